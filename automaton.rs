@@ -0,0 +1,345 @@
+use crate::common::grid::Grid;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Deref,
+};
+
+/// The neighbors of a cell, in the order of the offsets the automaton was built with. Cells
+/// outside a bounded grid are left out.
+pub struct Neighbors<'a, T>(pub &'a [T]);
+
+impl<T> Neighbors<'_, T> {
+    /// Number of neighbors equal to `value`.
+    pub fn count(&self, value: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.0.iter().filter(|&v| v == value).count()
+    }
+
+    pub fn count_by(&self, mut predicate: impl FnMut(&T) -> bool) -> usize {
+        self.0.iter().filter(|&v| predicate(v)).count()
+    }
+}
+
+impl<T> Deref for Neighbors<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+/// A cellular automaton over a dense [`Grid`].
+///
+/// The neighborhood is a list of `(row, column)` offsets, such as
+/// [`NEIGHBORS`](crate::common::utils::NEIGHBORS) or
+/// [`NEIGHBORS_DIAG`](crate::common::utils::NEIGHBORS_DIAG).
+///
+/// * `background`, when set the grid is surrounded by infinitely many cells of that value. The
+///   grid grows on every side by the reach of the offsets each step, then border rows and columns
+///   matching the updated background are trimmed, so only the region that differs from it is kept.
+///   The background itself is updated with the rule, so it can flip between states.
+///
+/// Only square grids are supported, use [`Sparse`] for hex or N-dimensional neighborhoods.
+#[derive(Debug, Clone)]
+pub struct Dense<'a, T> {
+    pub grid: Grid<T>,
+    pub background: Option<T>,
+    offsets: &'a [(isize, isize)],
+    origin: (isize, isize),
+}
+
+impl<'a, T> Dense<'a, T>
+where
+    T: Copy + Eq,
+{
+    /// A bounded automaton, cells past the edge of `grid` don't count as neighbors.
+    pub fn new(grid: Grid<T>, offsets: &'a [(isize, isize)]) -> Self {
+        Self {
+            grid,
+            background: None,
+            offsets,
+            origin: (0, 0),
+        }
+    }
+
+    /// An unbounded automaton, every cell outside of `grid` starts as `background`.
+    pub fn with_background(grid: Grid<T>, offsets: &'a [(isize, isize)], background: T) -> Self {
+        Self {
+            grid,
+            background: Some(background),
+            offsets,
+            origin: (0, 0),
+        }
+    }
+
+    /// Position in `grid` of the cell that started at `(0, 0)`. With a background the grid grows
+    /// and shrinks around it, so it may lie outside of the grid.
+    pub fn origin(&self) -> (isize, isize) {
+        self.origin
+    }
+
+    /// Applies `rule` to every cell at once. Returns whether anything changed.
+    pub fn step(&mut self, mut rule: impl FnMut(T, &Neighbors<T>) -> T) -> bool {
+        let pad = match self.background {
+            Some(_) => self
+                .offsets
+                .iter()
+                .map(|&(dr, dc)| dr.unsigned_abs().max(dc.unsigned_abs()))
+                .max()
+                .unwrap_or(0),
+            None => 0,
+        };
+        let width = self.grid.width() + 2 * pad;
+        let height = self.grid.height() + 2 * pad;
+
+        let grid = &self.grid;
+        let background = self.background;
+        let get = |r: isize, c: isize| grid.get_signed(r, c).copied().or(background);
+
+        let mut changed = false;
+        let mut buffer = Vec::with_capacity(self.offsets.len());
+        let data = (0..width * height)
+            .map(|i| {
+                let r = (i / width) as isize - pad as isize;
+                let c = (i % width) as isize - pad as isize;
+                let cell = get(r, c).unwrap();
+
+                buffer.clear();
+                buffer.extend(
+                    self.offsets
+                        .iter()
+                        .filter_map(|&(dr, dc)| get(r + dr, c + dc)),
+                );
+                let next = rule(cell, &Neighbors(&buffer));
+                changed |= next != cell;
+                next
+            })
+            .collect();
+        self.grid = Grid::new(width, height, data);
+
+        if let Some(background) = self.background {
+            let neighbors = vec![background; self.offsets.len()];
+            let next = rule(background, &Neighbors(&neighbors));
+            changed |= next != background;
+            self.background = Some(next);
+            self.origin = (self.origin.0 + pad as isize, self.origin.1 + pad as isize);
+            self.trim(next);
+        }
+
+        changed
+    }
+
+    /// Drops the border rows and columns made only of `background`.
+    fn trim(&mut self, background: T) {
+        let (mut top, mut bottom) = (usize::MAX, 0);
+        let (mut left, mut right) = (usize::MAX, 0);
+        for (r, c) in self.grid.position_all(|&v| v != background) {
+            top = top.min(r);
+            bottom = bottom.max(r + 1);
+            left = left.min(c);
+            right = right.max(c + 1);
+        }
+        if top == usize::MAX {
+            self.grid = Grid::new(0, 0, Vec::new());
+            return;
+        }
+
+        let data = self.grid.rows().take(bottom).skip(top);
+        let data = data.flat_map(|row| &row[left..right]).copied().collect();
+        self.grid = Grid::new(right - left, bottom - top, data);
+        self.origin = (self.origin.0 - top as isize, self.origin.1 - left as isize);
+    }
+
+    pub fn run(&mut self, steps: usize, mut rule: impl FnMut(T, &Neighbors<T>) -> T) {
+        for _ in 0..steps {
+            self.step(&mut rule);
+        }
+    }
+
+    /// Steps until nothing changes. Returns the number of steps taken, including the last one
+    /// that changed nothing.
+    pub fn run_until_fixpoint(&mut self, mut rule: impl FnMut(T, &Neighbors<T>) -> T) -> usize {
+        (1..).find(|_| !self.step(&mut rule)).unwrap()
+    }
+}
+
+/// A two state cellular automaton storing only the live cells, for patterns that grow without
+/// bounds.
+///
+/// * `neighbors`, returns the neighbors of a position. Any of the signed helpers in utils work,
+///   e.g. `|&(r, c)| neighbors_diag_signed(r, c)`, as do `Hex::neighbors` and `neighbors_nd_moore`.
+///
+/// Only live cells and their neighbors are visited, so the rule must keep dead cells without live
+/// neighbors dead.
+#[derive(Debug, Clone)]
+pub struct Sparse<P, FN> {
+    pub cells: HashSet<P>,
+    neighbors: FN,
+}
+
+impl<P, FN, IN> Sparse<P, FN>
+where
+    P: Eq + Hash + Clone,
+    FN: FnMut(&P) -> IN,
+    IN: IntoIterator<Item = P>,
+{
+    pub fn new(cells: impl IntoIterator<Item = P>, neighbors: FN) -> Self {
+        Self {
+            cells: cells.into_iter().collect(),
+            neighbors,
+        }
+    }
+
+    /// Applies `rule(alive, live_neighbors)` to every cell at once. Returns whether anything
+    /// changed.
+    pub fn step(&mut self, mut rule: impl FnMut(bool, usize) -> bool) -> bool {
+        let mut counts: HashMap<P, usize> = HashMap::with_capacity(self.cells.len() * 4);
+        for p in &self.cells {
+            counts.entry(p.clone()).or_insert(0);
+            for n in (self.neighbors)(p) {
+                *counts.entry(n).or_insert(0) += 1;
+            }
+        }
+
+        let next: HashSet<P> = counts
+            .into_iter()
+            .filter(|(p, n)| rule(self.cells.contains(p), *n))
+            .map(|(p, _)| p)
+            .collect();
+
+        let changed = next != self.cells;
+        self.cells = next;
+        changed
+    }
+
+    pub fn run(&mut self, steps: usize, mut rule: impl FnMut(bool, usize) -> bool) {
+        for _ in 0..steps {
+            self.step(&mut rule);
+        }
+    }
+
+    /// Steps until nothing changes. Returns the number of steps taken, including the last one
+    /// that changed nothing.
+    pub fn run_until_fixpoint(&mut self, mut rule: impl FnMut(bool, usize) -> bool) -> usize {
+        (1..).find(|_| !self.step(&mut rule)).unwrap()
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        hex::Hex,
+        utils::{NEIGHBORS_DIAG, neighbors_diag_signed, neighbors_nd_moore},
+    };
+
+    #[test]
+    fn test_seating() {
+        let input = "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..L..\nLLLL.LL.LL\nL.LL.LL.LL\n\
+                     L.LLLLL.LL\n..L.L.....\nLLLLLLLLLL\nL.LLLLLL.L\nL.LLLLL.LL";
+        let mut seats = Dense::new(input.parse().unwrap(), &NEIGHBORS_DIAG);
+        let steps = seats.run_until_fixpoint(|cell, n| match cell {
+            b'L' if n.count(&b'#') == 0 => b'#',
+            b'#' if n.count(&b'#') >= 4 => b'L',
+            _ => cell,
+        });
+
+        assert_eq!(steps, 6);
+        assert_eq!(seats.grid.position_all(|&b| b == b'#').count(), 37);
+    }
+
+    #[test]
+    fn test_flipping_background() {
+        // lit when an even number of the 3x3 window is lit, so an empty background turns lit and
+        // a lit one turns dark, like an enhancement algorithm starting with '#'
+        let rule =
+            |cell: bool, n: &Neighbors<bool>| (n.count(&true) + cell as usize).is_multiple_of(2);
+
+        let start: Grid<bool> = Grid::from_str_map("#..\n.##", |b| b == b'#');
+        let mut image = Dense::with_background(start.clone(), &NEIGHBORS_DIAG, false);
+
+        // evaluate every cell of the infinite plane directly
+        fn reference(start: &Grid<bool>, steps: usize, r: isize, c: isize) -> bool {
+            if steps == 0 {
+                return start.get_signed(r, c).copied().unwrap_or(false);
+            }
+            let lit = (-1..=1)
+                .flat_map(|dr| (-1..=1).map(move |dc| (dr, dc)))
+                .filter(|&(dr, dc)| reference(start, steps - 1, r + dr, c + dc))
+                .count();
+            lit % 2 == 0
+        }
+
+        for steps in 1..=4 {
+            image.step(rule);
+            assert_eq!(image.background, Some(steps % 2 == 1));
+            assert!(image.grid.height() <= 2 + 2 * steps && image.grid.width() <= 3 + 2 * steps);
+
+            // check a margin around the grid too, which must match the background
+            let (or, oc) = image.origin();
+            let (height, width) = (image.grid.height() as isize, image.grid.width() as isize);
+            for r in -2..height + 2 {
+                for c in -2..width + 2 {
+                    let cell = image.grid.get_signed(r, c).copied().or(image.background);
+                    assert_eq!(cell, Some(reference(&start, steps, r - or, c - oc)));
+                }
+            }
+        }
+
+        // a lone lit cell goes dark along with its surroundings, leaving nothing to keep
+        let mut dot = Dense::with_background(Grid::filled(1, 1, true), &NEIGHBORS_DIAG, false);
+        dot.step(|_, n| n.count(&true) == 8);
+        assert_eq!((dot.grid.height(), dot.grid.width()), (0, 0));
+    }
+
+    #[test]
+    fn test_sparse_life() {
+        let life = |alive: bool, n: usize| n == 3 || (alive && n == 2);
+
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        let mut plane = Sparse::new(glider, |&(r, c): &(i64, i64)| neighbors_diag_signed(r, c));
+        plane.run(8, life);
+        let expected: HashSet<_> = glider.iter().map(|&(r, c)| (r + 2, c + 2)).collect();
+        assert_eq!(plane.cells, expected);
+
+        let mut block = Sparse::new(
+            [(0, 0), (0, 1), (1, 0), (1, 1), (5, 5)],
+            |&(r, c): &(i64, i64)| neighbors_diag_signed(r, c),
+        );
+        assert_eq!(block.run_until_fixpoint(life), 2);
+        assert_eq!(block.cells.len(), 4);
+    }
+
+    #[test]
+    fn test_conway_cubes() {
+        let start = [[0, 1, 0], [1, 2, 0], [2, 0, 0], [2, 1, 0], [2, 2, 0]];
+        let rule = |alive: bool, n: usize| n == 3 || (alive && n == 2);
+
+        let mut cubes = Sparse::new(start, |&p: &[i32; 3]| neighbors_nd_moore(p));
+        cubes.run(6, rule);
+        assert_eq!(cubes.cells.len(), 112);
+
+        let start_4d = start.map(|[x, y, z]| [x, y, z, 0]);
+        let mut hypercubes = Sparse::new(start_4d, |&p: &[i32; 4]| neighbors_nd_moore(p));
+        hypercubes.run(6, rule);
+        assert_eq!(hypercubes.cells.len(), 848);
+    }
+
+    #[test]
+    fn test_hex() {
+        // black tiles flip to white with 0 or more than 2 black neighbors, white ones flip to
+        // black with exactly 2
+        let rule = |black: bool, n: usize| if black { n == 1 || n == 2 } else { n == 2 };
+
+        let mut tiles = Sparse::new([Hex::new(0, 0), Hex::new(1, 0)], |h: &Hex| h.neighbors());
+        tiles.step(rule);
+        // the pair survives and the two tiles touching both turn black
+        assert_eq!(tiles.cells.len(), 4);
+        assert!(tiles.cells.contains(&Hex::new(1, -1)));
+        assert!(tiles.cells.contains(&Hex::new(0, 1)));
+    }
+}
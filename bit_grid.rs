@@ -0,0 +1,427 @@
+use crate::common::grid::ParseGridError;
+use std::{
+    fmt,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
+    str::FromStr,
+};
+
+const BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans with every row packed into `u64` words, column `c` being bit `c % 64` of
+/// word `c / 64`.
+///
+/// Whole grids are combined with `&`, `|`, `^` and moved with [`BitGrid::shifted`], which makes
+/// cellular automata update 64 cells per operation. [`BitGrid::neighbor_counts`] sums shifted
+/// copies with a bit-sliced adder.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(BITS);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> bool) -> Self {
+        let mut grid = Self::new(width, height);
+        for r in 0..height {
+            for c in 0..width {
+                if f(r, c) {
+                    grid.set(r, c, true);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The packed words of row `r`. Bits past the width are always clear.
+    pub fn row_words(&self, r: usize) -> &[u64] {
+        &self.words[r * self.words_per_row..(r + 1) * self.words_per_row]
+    }
+
+    fn locate(&self, r: usize, c: usize) -> (usize, u64) {
+        assert!(r < self.height && c < self.width, "position out of bounds");
+        (r * self.words_per_row + c / BITS, 1 << (c % BITS))
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        let (i, bit) = self.locate(r, c);
+        self.words[i] & bit != 0
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        let (i, bit) = self.locate(r, c);
+        if value {
+            self.words[i] |= bit;
+        } else {
+            self.words[i] &= !bit;
+        }
+    }
+
+    /// Number of set cells.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Positions of the set cells in row-major order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let r = i / self.words_per_row;
+            let c0 = (i % self.words_per_row) * BITS;
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    (r, c0 + bit)
+                })
+            })
+        })
+    }
+
+    /// Mask of the valid bits in the last word of a row.
+    fn last_word_mask(&self) -> u64 {
+        match self.width % BITS {
+            0 => u64::MAX,
+            rem => (1 << rem) - 1,
+        }
+    }
+
+    fn clear_padding(&mut self) {
+        let mask = self.last_word_mask();
+        if self.words_per_row > 0 {
+            for row in self.words.chunks_exact_mut(self.words_per_row) {
+                *row.last_mut().unwrap() &= mask;
+            }
+        }
+    }
+
+    /// Moves every cell by `dr` rows and `dc` columns. Cells pushed past an edge are dropped and
+    /// the vacated cells are clear.
+    pub fn shifted(&self, dr: isize, dc: isize) -> Self {
+        let mut out = Self::new(self.width, self.height);
+        let n = self.words_per_row;
+        let word_shift = dc.unsigned_abs() / BITS;
+        let bit_shift = dc.unsigned_abs() % BITS;
+
+        for r in 0..self.height {
+            let Some(src) = r.checked_add_signed(-dr).filter(|&src| src < self.height) else {
+                continue;
+            };
+            let src = self.row_words(src);
+            let dst = &mut out.words[r * n..(r + 1) * n];
+            let word = |i: Option<usize>| i.and_then(|i| src.get(i)).copied().unwrap_or(0);
+
+            for (w, d) in dst.iter_mut().enumerate() {
+                *d = if dc >= 0 {
+                    // towards higher columns, so bits come from lower words
+                    let lo = w.checked_sub(word_shift);
+                    let carry = lo.and_then(|i| i.checked_sub(1));
+                    match bit_shift {
+                        0 => word(lo),
+                        s => (word(lo) << s) | (word(carry) >> (BITS - s)),
+                    }
+                } else {
+                    let hi = Some(w + word_shift);
+                    let carry = Some(w + word_shift + 1);
+                    match bit_shift {
+                        0 => word(hi),
+                        s => (word(hi) >> s) | (word(carry) << (BITS - s)),
+                    }
+                };
+            }
+        }
+
+        out.clear_padding();
+        out
+    }
+
+    /// Cells set in `self` but not in `other`.
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "grid sizes differ"
+        );
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    /// Counts for every cell how many of the cells at the given offsets are set, e.g. with
+    /// [`NEIGHBORS_DIAG`](crate::common::utils::NEIGHBORS_DIAG) for the Moore neighborhood.
+    /// Offsets are `(row, column)` deltas pointing from a cell to its neighbor.
+    pub fn neighbor_counts(&self, offsets: &[(isize, isize)]) -> NeighborCounts {
+        let mut planes: Vec<BitGrid> = Vec::new();
+        for &(dr, dc) in offsets {
+            // the neighbor at +d lands on the cell when shifting by -d
+            let mut carry = self.shifted(-dr, -dc);
+            for plane in planes.iter_mut() {
+                let next = &*plane & &carry;
+                *plane ^= &carry;
+                carry = next;
+                if carry.is_empty() {
+                    break;
+                }
+            }
+            if !carry.is_empty() {
+                planes.push(carry);
+            }
+        }
+
+        NeighborCounts {
+            empty: Self::new(self.width, self.height),
+            planes,
+        }
+    }
+}
+
+/// Per-cell counts stored as bit planes, `planes[k]` holding bit `k` of every count.
+#[derive(Debug, Clone)]
+pub struct NeighborCounts {
+    empty: BitGrid,
+    planes: Vec<BitGrid>,
+}
+
+impl NeighborCounts {
+    /// Cells whose count is exactly `n`.
+    pub fn equals(&self, n: usize) -> BitGrid {
+        if n >> self.planes.len() != 0 {
+            return self.empty.clone();
+        }
+        self.planes
+            .iter()
+            .enumerate()
+            .fold(!&self.empty, |acc, (k, plane)| {
+                if n >> k & 1 == 1 {
+                    &acc & plane
+                } else {
+                    acc.and_not(plane)
+                }
+            })
+    }
+
+    /// Cells whose count is any of `ns`.
+    pub fn any_of(&self, ns: &[usize]) -> BitGrid {
+        ns.iter()
+            .fold(self.empty.clone(), |acc, &n| &acc | &self.equals(n))
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> usize {
+        self.planes
+            .iter()
+            .enumerate()
+            .map(|(k, plane)| (plane.get(r, c) as usize) << k)
+            .sum()
+    }
+}
+
+macro_rules! bit_ops {
+    ($($op:ident, $method:ident, $assign_op:ident, $assign_method:ident, $f:expr);+ $(;)?) => {
+        $(
+            impl $op for &BitGrid {
+                type Output = BitGrid;
+
+                fn $method(self, rhs: Self) -> Self::Output {
+                    self.zip_with(rhs, $f)
+                }
+            }
+
+            impl $assign_op<&BitGrid> for BitGrid {
+                fn $assign_method(&mut self, rhs: &BitGrid) {
+                    *self = (&*self).$method(rhs);
+                }
+            }
+        )+
+    };
+}
+
+bit_ops!(
+    BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b;
+    BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b;
+    BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b;
+);
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> Self::Output {
+        let mut out = BitGrid {
+            words: self.words.iter().map(|w| !w).collect(),
+            ..*self
+        };
+        out.clear_padding();
+        out
+    }
+}
+
+impl FromStr for BitGrid {
+    type Err = ParseGridError;
+
+    /// Parses one row per line, `#` is set and anything else (usually `.`) is clear.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&[u8]> = s.lines().map(str::as_bytes).collect();
+        let width = lines.first().map_or(0, |l| l.len());
+        if lines.iter().any(|l| l.len() != width) {
+            return Err(ParseGridError);
+        }
+        Ok(Self::from_fn(width, lines.len(), |r, c| {
+            lines[r][c] == b'#'
+        }))
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.height {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            for c in 0..self.width {
+                write!(f, "{}", if self.get(r, c) { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::utils::{NEIGHBORS, NEIGHBORS_DIAG, neighbors_diag};
+
+    fn life(grid: &BitGrid) -> BitGrid {
+        let counts = grid.neighbor_counts(&NEIGHBORS_DIAG);
+        &counts.equals(3) | &(grid & &counts.equals(2))
+    }
+
+    #[test]
+    fn test_parse_get_set() {
+        let mut grid: BitGrid = "#..\n.#.\n..#".parse().unwrap();
+        assert_eq!((grid.height(), grid.width()), (3, 3));
+        assert!(grid.get(1, 1));
+        assert!(!grid.get(0, 1));
+        assert_eq!(grid.count_ones(), 3);
+
+        grid.set(0, 2, true);
+        grid.set(0, 0, false);
+        assert_eq!(grid.to_string(), "..#\n.#.\n..#");
+        assert_eq!(
+            grid.iter_ones().collect::<Vec<_>>(),
+            vec![(0, 2), (1, 1), (2, 2)]
+        );
+        assert_eq!((!&grid).count_ones(), 6);
+        assert_eq!("#.\n#".parse::<BitGrid>(), Err(ParseGridError));
+    }
+
+    #[test]
+    fn test_shifts() {
+        let grid: BitGrid = "##.\n#..\n...".parse().unwrap();
+        assert_eq!(grid.shifted(1, 0).to_string(), "...\n##.\n#..");
+        assert_eq!(grid.shifted(0, 1).to_string(), ".##\n.#.\n...");
+        assert_eq!(grid.shifted(-1, -1).to_string(), "...\n...\n...");
+        assert_eq!(grid.shifted(1, 2).to_string(), "...\n..#\n..#");
+
+        // crossing word boundaries
+        let wide = BitGrid::from_fn(150, 2, |r, c| r == 0 && c % 7 == 0);
+        for dc in [-130, -64, -63, -1, 1, 63, 64, 65, 130] {
+            let shifted = wide.shifted(1, dc);
+            let expected = BitGrid::from_fn(150, 2, |r, c| {
+                r == 1
+                    && c.checked_add_signed(-dc)
+                        .is_some_and(|src| src < 150 && src % 7 == 0)
+            });
+            assert_eq!(shifted, expected, "dc = {dc}");
+        }
+    }
+
+    #[test]
+    fn test_bit_ops() {
+        let a: BitGrid = "##..".parse().unwrap();
+        let b: BitGrid = "#.#.".parse().unwrap();
+        assert_eq!((&a & &b).to_string(), "#...");
+        assert_eq!((&a | &b).to_string(), "###.");
+        assert_eq!((&a ^ &b).to_string(), ".##.");
+        assert_eq!(a.and_not(&b).to_string(), ".#..");
+
+        let mut c = a.clone();
+        c ^= &a;
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_neighbor_counts() {
+        // compare against neighbors_diag on a pseudo random grid wider than a word
+        let mut seed = 12345u64;
+        let grid = BitGrid::from_fn(70, 9, |_, _| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed >> 62 == 0
+        });
+        let counts = grid.neighbor_counts(&NEIGHBORS_DIAG);
+        for r in 0..9 {
+            for c in 0..70 {
+                let expected = neighbors_diag(r, c, 9, 70)
+                    .filter(|&(nr, nc)| grid.get(nr, nc))
+                    .count();
+                assert_eq!(counts.get(r, c), expected);
+                assert!(counts.equals(expected).get(r, c));
+            }
+        }
+        assert_eq!(counts.equals(9).count_ones(), 0);
+
+        let orthogonal = grid.neighbor_counts(&NEIGHBORS);
+        let any = orthogonal.any_of(&[1, 2, 3, 4]);
+        assert_eq!(any, !&orthogonal.equals(0));
+    }
+
+    #[test]
+    fn test_life() {
+        let blinker: BitGrid = ".....\n..#..\n..#..\n..#..\n.....".parse().unwrap();
+        assert_eq!(
+            life(&blinker).to_string(),
+            ".....\n.....\n.###.\n.....\n....."
+        );
+        assert_eq!(life(&life(&blinker)), blinker);
+
+        let glider: BitGrid = ".#....\n..#...\n###...\n......\n......\n......"
+            .parse()
+            .unwrap();
+        let moved = (0..4).fold(glider.clone(), |g, _| life(&g));
+        assert_eq!(moved, glider.shifted(1, 1));
+    }
+}
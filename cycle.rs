@@ -0,0 +1,229 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// The states visited from a starting state until the first repeat. `states[mu..]` is the cycle,
+/// which has length `lambda`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N> {
+    pub mu: usize,
+    pub lambda: usize,
+    pub states: Vec<N>,
+}
+
+impl<N> Cycle<N> {
+    /// Returns the state after `n` steps from the start.
+    pub fn state_after(&self, n: usize) -> &N {
+        &self.states[cycle_index(self.mu, self.lambda, n).0]
+    }
+}
+
+/// Maps step `n` of a sequence with a cycle starting at `mu` of length `lambda` to the equivalent
+/// step in `0..mu + lambda`. Also returns the number of whole cycles that were skipped, which is
+/// handy for extrapolating values that grow by a fixed amount every cycle.
+pub fn cycle_index(mu: usize, lambda: usize, n: usize) -> (usize, usize) {
+    if n < mu {
+        (n, 0)
+    } else {
+        (mu + (n - mu) % lambda, (n - mu) / lambda)
+    }
+}
+
+/// Returns the state after `n` steps, stepping at most `mu + lambda` times.
+pub fn state_after<N, FN>(start: &N, mut step: FN, mu: usize, lambda: usize, n: usize) -> N
+where
+    N: Clone,
+    FN: FnMut(&N) -> N,
+{
+    let (n, _) = cycle_index(mu, lambda, n);
+    (0..n).fold(start.clone(), |state, _| step(&state))
+}
+
+/// `floyd` finds the cycle reached from `start` using Floyd's tortoise and hare algorithm, without
+/// storing any states. Returns `(mu, lambda)`, the index of the first state in the cycle and the
+/// length of the cycle.
+pub fn floyd<N, FN>(start: &N, mut step: FN) -> (usize, usize)
+where
+    N: Eq + Clone,
+    FN: FnMut(&N) -> N,
+{
+    let mut tortoise = step(start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut mu = 0;
+    tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+
+    let mut lambda = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        lambda += 1;
+    }
+
+    (mu, lambda)
+}
+
+/// `brent` finds the cycle reached from `start` using Brent's algorithm, which needs fewer steps
+/// than [`floyd`]. Returns `(mu, lambda)`.
+pub fn brent<N, FN>(start: &N, mut step: FN) -> (usize, usize)
+where
+    N: Eq + Clone,
+    FN: FnMut(&N) -> N,
+{
+    let mut power = 1;
+    let mut lambda = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+        hare = step(&hare);
+        lambda += 1;
+    }
+
+    tortoise = start.clone();
+    hare = (0..lambda).fold(start.clone(), |state, _| step(&state));
+
+    let mut mu = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        mu += 1;
+    }
+
+    (mu, lambda)
+}
+
+/// `find_cycle` records every state until the projection `key` of a state repeats.
+///
+/// * `key`, two states are considered equal when their keys are. This allows leaving out parts of
+///   the state that don't affect the future, like the height of a tower.
+pub fn find_cycle<N, K, FN, FK>(start: N, mut step: FN, mut key: FK) -> Cycle<N>
+where
+    K: Eq + Hash,
+    FN: FnMut(&N) -> N,
+    FK: FnMut(&N) -> K,
+{
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = start;
+
+    loop {
+        let k = key(&state);
+        if let Some(&mu) = seen.get(&k) {
+            return Cycle {
+                mu,
+                lambda: states.len() - mu,
+                states,
+            };
+        }
+        seen.insert(k, states.len());
+
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+/// `find_cycle_mapper` is [`find_cycle`] using a `Vec` indexed by `mapper` instead of hashing.
+///
+/// * `mapper`, must map to a usize. This is useful for dense states, like an index into a grid.
+///   Values should be tightly packed, otherwise the lookup table may grow too large.
+pub fn find_cycle_mapper<N, FN, FM>(start: N, mut step: FN, mut mapper: FM) -> Cycle<N>
+where
+    FN: FnMut(&N) -> N,
+    FM: FnMut(&N) -> usize,
+{
+    let mut seen = Vec::new();
+    let mut states = Vec::new();
+    let mut state = start;
+
+    loop {
+        let v = mapper(&state);
+        if v >= seen.len() {
+            seen.resize(v + 1, usize::MAX);
+        }
+        if seen[v] != usize::MAX {
+            let mu = seen[v];
+            return Cycle {
+                mu,
+                lambda: states.len() - mu,
+                states,
+            };
+        }
+        seen[v] = states.len();
+
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3 -> 10 -> 101 -> 2 -> 5 -> 26 -> 167 -> 95 -> 101 -> ... so mu = 2, lambda = 6
+    fn step(x: &u32) -> u32 {
+        (x * x + 1) % 255
+    }
+
+    #[test]
+    fn test_floyd_and_brent() {
+        assert_eq!(floyd(&3, step), (2, 6));
+        assert_eq!(brent(&3, step), (2, 6));
+
+        // pure cycle
+        assert_eq!(floyd(&0, |x| (x + 1) % 7), (0, 7));
+        assert_eq!(brent(&0, |x| (x + 1) % 7), (0, 7));
+
+        // fixed point
+        assert_eq!(floyd(&5, |_| 1), (1, 1));
+        assert_eq!(brent(&5, |_| 1), (1, 1));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = find_cycle(3, step, |&x| x);
+        assert_eq!((cycle.mu, cycle.lambda), (2, 6));
+        assert_eq!(cycle.states.len(), 8);
+        assert_eq!(&cycle.states[..4], &[3, 10, 101, 2]);
+
+        let cycle = find_cycle_mapper(3, step, |&x| x as usize);
+        assert_eq!((cycle.mu, cycle.lambda), (2, 6));
+
+        let n = 1_000_000_000;
+        assert_eq!(*cycle.state_after(n), 5);
+        assert_eq!(state_after(&3, step, 2, 6, n), 5);
+        assert_eq!(*cycle.state_after(1), 10);
+    }
+
+    #[test]
+    fn test_find_cycle_projection() {
+        // (position, total) where only the position determines the future
+        let cycle = find_cycle(
+            (0, 0u64),
+            |&(pos, total)| ((pos + 1) % 5, total + pos),
+            |&(pos, _)| pos,
+        );
+        assert_eq!((cycle.mu, cycle.lambda), (0, 5));
+
+        // the total grows by 0 + 1 + 2 + 3 + 4 = 10 every cycle
+        let n = 1_000_000_000_003;
+        let (i, cycles) = cycle_index(cycle.mu, cycle.lambda, n);
+        assert_eq!(i, 3);
+        assert_eq!(cycle.states[i].1 + 10 * cycles as u64, 2_000_000_000_003);
+    }
+}
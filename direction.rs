@@ -0,0 +1,388 @@
+use crate::common::AddIsize;
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+/// Common behaviour of [`Dir4`] and [`Dir8`]. Deltas are `(row, column)` offsets with rows
+/// growing downwards, matching [`neighbors_arbitray`](crate::common::utils::neighbors_arbitray).
+pub trait Direction: Copy + Sized + 'static {
+    /// Every direction, in clockwise order starting at north.
+    const ALL: &'static [Self];
+
+    /// Position of the direction in [`Direction::ALL`].
+    fn index(self) -> usize;
+
+    fn delta(self) -> (isize, isize);
+
+    /// Moves one step from `(r, c)`, returning `None` when leaving `0..r_max` x `0..c_max`.
+    fn step(self, r: usize, c: usize, r_max: usize, c_max: usize) -> Option<(usize, usize)> {
+        let (dr, dc) = self.delta();
+        Some((
+            r.checked_add_isize_clamp(dr, r_max)?,
+            c.checked_add_isize_clamp(dc, c_max)?,
+        ))
+    }
+
+    fn iter() -> impl Iterator<Item = Self> {
+        Self::ALL.iter().copied()
+    }
+
+    fn turn_right(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    fn turn_left(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn reverse(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() / 2) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction for Dir4 {
+    const ALL: &'static [Self] = &[Self::North, Self::East, Self::South, Self::West];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::East => (0, 1),
+            Self::South => (1, 0),
+            Self::West => (0, -1),
+        }
+    }
+}
+
+impl Dir4 {
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Self::North | Self::South)
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        !self.is_vertical()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction for Dir8 {
+    const ALL: &'static [Self] = &[
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::NorthEast => (-1, 1),
+            Self::East => (0, 1),
+            Self::SouthEast => (1, 1),
+            Self::South => (1, 0),
+            Self::SouthWest => (1, -1),
+            Self::West => (0, -1),
+            Self::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(value: Dir4) -> Self {
+        Self::ALL[value.index() * 2]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectionError;
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid direction")
+    }
+}
+
+impl std::error::Error for ParseDirectionError {}
+
+impl TryFrom<u8> for Dir4 {
+    type Error = ParseDirectionError;
+
+    /// Parses arrows `^>v<`, `UDLR` and `NESW`.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'^' | b'U' | b'N' => Ok(Self::North),
+            b'>' | b'R' | b'E' => Ok(Self::East),
+            b'v' | b'D' | b'S' => Ok(Self::South),
+            b'<' | b'L' | b'W' => Ok(Self::West),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+impl TryFrom<char> for Dir4 {
+    type Error = ParseDirectionError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        u8::try_from(value)
+            .map_err(|_| ParseDirectionError)
+            .and_then(Self::try_from)
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [b] => Self::try_from(*b),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+impl FromStr for Dir8 {
+    type Err = ParseDirectionError;
+
+    /// Parses compass directions like `N` and `SW`, as well as anything [`Dir4`] accepts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NE" => Ok(Self::NorthEast),
+            "SE" => Ok(Self::SouthEast),
+            "SW" => Ok(Self::SouthWest),
+            "NW" => Ok(Self::NorthWest),
+            _ => s.parse::<Dir4>().map(Self::from),
+        }
+    }
+}
+
+/// A set of directions stored as bit flags, e.g. to track which ways a cell was already crossed.
+pub struct DirSet<D> {
+    bits: u8,
+    _marker: PhantomData<D>,
+}
+
+impl<D: Direction> DirSet<D> {
+    pub fn new() -> Self {
+        Self::from_bits(0)
+    }
+
+    pub fn all() -> Self {
+        Self::from_bits(((1u16 << D::ALL.len()) - 1) as u8)
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            bits,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Adds `dir`, returning `true` if it wasn't in the set already.
+    pub fn insert(&mut self, dir: D) -> bool {
+        let bit = 1 << dir.index();
+        let inserted = self.bits & bit == 0;
+        self.bits |= bit;
+        inserted
+    }
+
+    /// Removes `dir`, returning `true` if it was in the set.
+    pub fn remove(&mut self, dir: D) -> bool {
+        let bit = 1 << dir.index();
+        let removed = self.bits & bit != 0;
+        self.bits &= !bit;
+        removed
+    }
+
+    pub fn contains(&self, dir: D) -> bool {
+        self.bits & (1 << dir.index()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = D> + '_ {
+        D::iter().filter(|&d| self.contains(d))
+    }
+}
+
+impl<D: Direction> Default for DirSet<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Clone for DirSet<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for DirSet<D> {}
+
+impl<D> PartialEq for DirSet<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<D> Eq for DirSet<D> {}
+
+impl<D> std::hash::Hash for DirSet<D> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+impl<D: Direction + fmt::Debug> fmt::Debug for DirSet<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<D: Direction> FromIterator<D> for DirSet<D> {
+    fn from_iter<I: IntoIterator<Item = D>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for d in iter {
+            set.insert(d);
+        }
+        set
+    }
+}
+
+impl<D> std::ops::BitOr for DirSet<D> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self {
+            bits: self.bits | rhs.bits,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<D> std::ops::BitAnd for DirSet<D> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self {
+            bits: self.bits & rhs.bits,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir4_turns() {
+        assert_eq!(Dir4::North.turn_right(), Dir4::East);
+        assert_eq!(Dir4::North.turn_left(), Dir4::West);
+        assert_eq!(Dir4::West.turn_right(), Dir4::North);
+        assert_eq!(Dir4::East.reverse(), Dir4::West);
+        assert_eq!(Dir4::South.delta(), (1, 0));
+        assert!(Dir4::South.is_vertical());
+        assert!(Dir4::West.is_horizontal());
+        assert_eq!(Dir4::West.step(2, 0, 3, 3), None);
+        assert_eq!(Dir4::South.step(1, 0, 3, 3), Some((2, 0)));
+        assert_eq!(Dir4::South.step(2, 0, 3, 3), None);
+
+        for d in Dir4::iter() {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.reverse().reverse(), d);
+            let (dr, dc) = d.delta();
+            assert_eq!(d.reverse().delta(), (-dr, -dc));
+        }
+    }
+
+    #[test]
+    fn test_dir8_turns() {
+        assert_eq!(Dir8::North.turn_right(), Dir8::NorthEast);
+        assert_eq!(Dir8::North.turn_left(), Dir8::NorthWest);
+        assert_eq!(Dir8::SouthEast.reverse(), Dir8::NorthWest);
+        assert_eq!(Dir8::from(Dir4::West), Dir8::West);
+        assert_eq!(Dir8::iter().count(), 8);
+        assert_eq!(Dir8::NorthWest.delta(), (-1, -1));
+    }
+
+    #[test]
+    fn test_parse() {
+        let arrows: Vec<Dir4> = "^>v<".bytes().map(|b| Dir4::try_from(b).unwrap()).collect();
+        assert_eq!(arrows, Dir4::ALL);
+        assert_eq!("U".parse(), Ok(Dir4::North));
+        assert_eq!("D".parse(), Ok(Dir4::South));
+        assert_eq!("L".parse(), Ok(Dir4::West));
+        assert_eq!("E".parse(), Ok(Dir4::East));
+        assert_eq!(Dir4::try_from('R'), Ok(Dir4::East));
+        assert_eq!("x".parse::<Dir4>(), Err(ParseDirectionError));
+        assert_eq!("NE".parse::<Dir4>(), Err(ParseDirectionError));
+
+        assert_eq!("NE".parse(), Ok(Dir8::NorthEast));
+        assert_eq!("W".parse(), Ok(Dir8::West));
+        assert_eq!("NNE".parse::<Dir8>(), Err(ParseDirectionError));
+    }
+
+    #[test]
+    fn test_dir_set() {
+        let mut set = DirSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(Dir4::North));
+        assert!(!set.insert(Dir4::North));
+        assert!(set.insert(Dir4::West));
+        assert!(set.contains(Dir4::West));
+        assert!(!set.contains(Dir4::East));
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Dir4::North, Dir4::West]
+        );
+
+        assert!(set.remove(Dir4::North));
+        assert!(!set.remove(Dir4::North));
+
+        let other: DirSet<Dir4> = [Dir4::East, Dir4::West].into_iter().collect();
+        assert_eq!((set | other).len(), 2);
+        assert_eq!((set & other).iter().collect::<Vec<_>>(), vec![Dir4::West]);
+        assert_eq!(DirSet::<Dir8>::all().len(), 8);
+        assert_eq!(format!("{:?}", other), "{East, West}");
+    }
+}
@@ -0,0 +1,466 @@
+use crate::common::{
+    matrix::{self, d4::D4},
+    point::Point2,
+    utils,
+};
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+/// A rectangular grid stored row-major in a flat `Vec`. Positions are `(row, column)` tuples or
+/// [`Point2<usize>`] with `x` as the row, the same as the `neighbors` helpers in utils.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGridError;
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "grid rows have different lengths")
+    }
+}
+
+impl std::error::Error for ParseGridError {}
+
+impl<T> Grid<T> {
+    /// Wraps row-major `data`. Panics if `data` doesn't have `width * height` elements.
+    pub fn new(width: usize, height: usize, data: Vec<T>) -> Self {
+        assert_eq!(data.len(), width * height, "data doesn't match dimensions");
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    fn try_from_str_map(s: &str, mut mapper: impl FnMut(u8) -> T) -> Result<Self, ParseGridError> {
+        let mut data = Vec::with_capacity(s.len());
+        let mut width = None;
+        let mut height = 0;
+
+        for line in s.lines() {
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err(ParseGridError);
+            }
+            data.extend(line.bytes().map(&mut mapper));
+            height += 1;
+        }
+
+        Ok(Self::new(width.unwrap_or(0), height, data))
+    }
+
+    /// Parses one row per line, mapping every byte with `mapper`. Panics if the lines don't all
+    /// have the same length.
+    pub fn from_str_map(s: &str, mapper: impl FnMut(u8) -> T) -> Self {
+        Self::try_from_str_map(s, mapper).unwrap()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn contains(&self, pos: impl Into<(usize, usize)>) -> bool {
+        let (r, c) = pos.into();
+        r < self.height && c < self.width
+    }
+
+    /// Index of `pos` in the flat storage, useful as a `mapper` for the pathfinding helpers.
+    pub fn index_of(&self, pos: impl Into<(usize, usize)>) -> usize {
+        let (r, c) = pos.into();
+        r * self.width + c
+    }
+
+    /// Inverse of [`Grid::index_of`].
+    pub fn position_of(&self, index: usize) -> (usize, usize) {
+        (index / self.width, index % self.width)
+    }
+
+    pub fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        let pos = pos.into();
+        self.contains(pos).then(|| &self.data[self.index_of(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<(usize, usize)>) -> Option<&mut T> {
+        let pos = pos.into();
+        if self.contains(pos) {
+            let i = self.index_of(pos);
+            Some(&mut self.data[i])
+        } else {
+            None
+        }
+    }
+
+    /// Like [`Grid::get`] with signed coordinates, so moving off the top or left edge doesn't need
+    /// special casing.
+    pub fn get_signed(&self, r: isize, c: isize) -> Option<&T> {
+        self.get((usize::try_from(r).ok()?, usize::try_from(c).ok()?))
+    }
+
+    /// Position of the first cell matching `predicate`, in row-major order.
+    pub fn find(&self, predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.data
+            .iter()
+            .position(predicate)
+            .map(|i| self.position_of(i))
+    }
+
+    /// Positions of every cell matching `predicate`, in row-major order.
+    pub fn position_all<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(move |(_, v)| predicate(v))
+            .map(|(i, _)| self.position_of(i))
+    }
+
+    /// Every cell along with its position, in row-major order.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (self.position_of(i), v))
+    }
+
+    pub fn neighbors(
+        &self,
+        pos: impl Into<(usize, usize)>,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (r, c) = pos.into();
+        utils::neighbors(r, c, self.height, self.width)
+    }
+
+    pub fn neighbors_diag(
+        &self,
+        pos: impl Into<(usize, usize)>,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (r, c) = pos.into();
+        utils::neighbors_diag(r, c, self.height, self.width)
+    }
+
+    pub fn neighbors_arbitrary<'a>(
+        &self,
+        diffs: &'a [(isize, isize)],
+        pos: impl Into<(usize, usize)>,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let (r, c) = pos.into();
+        utils::neighbors_arbitray(diffs, r, c, self.height, self.width)
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.data[r * self.width..(r + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [T] {
+        &mut self.data[r * self.width..(r + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks_exact panics on a zero chunk size, and a grid of width 0 has no data to chunk but
+        // still has `height` empty rows
+        self.data
+            .chunks_exact(self.width.max(1))
+            .chain(std::iter::repeat_with(<&[T]>::default))
+            .take(self.height)
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let height = self.height;
+        self.data
+            .chunks_exact_mut(self.width.max(1))
+            .chain(std::iter::repeat_with(<&mut [T]>::default))
+            .take(height)
+    }
+
+    pub fn column(&self, c: usize) -> impl Iterator<Item = &T> {
+        assert!(c < self.width, "column out of bounds");
+        self.data.iter().skip(c).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|c| self.column(c))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.data.iter().map(f).collect())
+    }
+
+    /// Reverses every row, mirroring the grid left to right.
+    pub fn flip(&mut self) {
+        self.rows_mut().for_each(<[T]>::reverse);
+    }
+
+    /// Rotates the grid 90° clockwise in place with [`matrix::rotate_right_flat`].
+    pub fn rotate_right_in_place(&mut self) {
+        (self.width, self.height) =
+            matrix::rotate_right_flat(&mut self.data, self.width, self.height);
+    }
+
+    /// Rotates the grid 90° counter-clockwise in place with [`matrix::rotate_left_flat`].
+    pub fn rotate_left_in_place(&mut self) {
+        (self.width, self.height) =
+            matrix::rotate_left_flat(&mut self.data, self.width, self.height);
+    }
+
+    /// Rotates the grid 180° in place.
+    pub fn rotate_bottom_in_place(&mut self) {
+        self.data.reverse();
+    }
+
+    /// Swaps rows and columns in place with [`matrix::transpose_flat`].
+    pub fn transpose_in_place(&mut self) {
+        (self.width, self.height) = matrix::transpose_flat(&mut self.data, self.width, self.height);
+    }
+
+    /// Returns a copy rotated 90° clockwise.
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        let mut rotated = self.clone();
+        rotated.rotate_right_in_place();
+        rotated
+    }
+
+    /// Returns a copy rotated 90° counter-clockwise.
+    pub fn rotate_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        let mut rotated = self.clone();
+        rotated.rotate_left_in_place();
+        rotated
+    }
+
+    /// Returns a copy rotated 180°.
+    pub fn rotate_bottom(&self) -> Self
+    where
+        T: Clone,
+    {
+        let mut rotated = self.clone();
+        rotated.rotate_bottom_in_place();
+        rotated
+    }
+
+    /// Applies any of the 8 rotations and reflections, see [`D4`].
+    pub fn transform(&self, d: D4) -> Self
+    where
+        T: Clone,
+    {
+        let (height, width) = d.dims((self.height, self.width));
+        let inverse = d.inverse();
+        let data = (0..height * width)
+            .map(|i| {
+                let src = inverse.transform((i / width, i % width), (height, width));
+                self[src].clone()
+            })
+            .collect();
+        Self::new(width, height, data)
+    }
+}
+
+impl<T> From<Vec<Vec<T>>> for Grid<T> {
+    /// Panics if the rows don't all have the same length.
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "{ParseGridError}"
+        );
+        Self::new(width, height, rows.into_iter().flatten().collect())
+    }
+}
+
+impl FromStr for Grid<u8> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str_map(s, |b| b)
+    }
+}
+
+impl<T, P> Index<P> for Grid<T>
+where
+    P: Into<(usize, usize)>,
+{
+    type Output = T;
+
+    fn index(&self, pos: P) -> &Self::Output {
+        let (r, c) = pos.into();
+        assert!(r < self.height && c < self.width, "position out of bounds");
+        &self.data[r * self.width + c]
+    }
+}
+
+impl<T, P> IndexMut<P> for Grid<T>
+where
+    P: Into<(usize, usize)>,
+{
+    fn index_mut(&mut self, pos: P) -> &mut Self::Output {
+        let (r, c) = pos.into();
+        assert!(r < self.height && c < self.width, "position out of bounds");
+        &mut self.data[r * self.width + c]
+    }
+}
+
+impl<T> fmt::Display for Grid<T>
+where
+    T: Copy + Into<char>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for &v in row {
+                write!(f, "{}", v.into())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Grid<u8> {
+    /// Position of the first occurrence of `byte`, e.g. the `S` start tile.
+    pub fn find_byte(&self, byte: u8) -> Option<Point2<usize>> {
+        self.find(|&b| b == byte).map(Point2::from)
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "#.S\n..#\n.E.\n#..\n";
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid: Grid<u8> = INPUT.parse().unwrap();
+        assert_eq!((grid.height(), grid.width()), (4, 3));
+        assert_eq!(grid[(0, 2)], b'S');
+        assert_eq!(grid[Point2::new(2, 1)], b'E');
+        assert_eq!(grid.get((3, 0)), Some(&b'#'));
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.get_signed(-1, 0), None);
+        assert_eq!(grid.get_signed(1, 2), Some(&b'#'));
+        assert_eq!(grid.find_byte(b'E'), Some(Point2::new(2, 1)));
+        assert_eq!(grid.to_string(), INPUT.trim_end());
+
+        assert_eq!("ab\nc".parse::<Grid<u8>>(), Err(ParseGridError));
+
+        let walls = Grid::from_str_map(INPUT, |b| b == b'#');
+        assert_eq!(
+            walls.position_all(|&w| w).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 2), (3, 0)]
+        );
+        assert_eq!(walls.find(|&w| !w), Some((0, 1)));
+        assert_eq!(walls.indexed_iter().filter(|(_, w)| **w).count(), 3);
+
+        let mut grid = grid;
+        grid[(1, 1)] = b'O';
+        *grid.get_mut((0, 0)).unwrap() = b'.';
+        assert_eq!(grid.row(1), b".O#");
+        assert_eq!(grid.index_of((1, 1)), 4);
+        assert_eq!(grid.position_of(4), (1, 1));
+    }
+
+    #[test]
+    fn test_rows_columns_and_neighbors() {
+        let grid: Grid<u8> = INPUT.parse().unwrap();
+        assert_eq!(grid.rows().count(), 4);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), b"S#..");
+        assert_eq!(
+            grid.columns().map(|c| c.count()).collect::<Vec<_>>(),
+            vec![4, 4, 4]
+        );
+
+        let mut n: Vec<_> = grid.neighbors((0, 2)).collect();
+        n.sort();
+        assert_eq!(n, vec![(0, 1), (1, 2)]);
+        assert_eq!(grid.neighbors_diag(Point2::new(1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbors_arbitrary(&[(1, 0), (0, 1)], (3, 2)).count(),
+            0
+        );
+
+        let doubled = grid.map(|&b| (b == b'.') as u8 * 2);
+        assert_eq!(doubled.as_slice().iter().filter(|&&v| v == 2).count(), 7);
+
+        // rows of width 0 still count towards the height
+        let mut empty: Grid<u8> = Grid::new(0, 3, Vec::new());
+        assert_eq!(empty.rows().count(), 3);
+        assert_eq!(empty.rows_mut().count(), 3);
+        assert_eq!(empty.to_string(), "\n\n");
+        assert_eq!(Grid::<u8>::new(3, 0, Vec::new()).rows().count(), 0);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let mut grid: Grid<u8> = "abc\ndef".parse().unwrap();
+        assert_eq!(grid.rotate_right().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_left().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_bottom().to_string(), "fed\ncba");
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+        assert_eq!(
+            grid.rotate_right(),
+            matrix::rotate_right_m_n(&grid.rows().collect::<Vec<_>>()).into()
+        );
+
+        let mut turned = grid.clone();
+        turned.transpose_in_place();
+        assert_eq!((turned.height(), turned.width()), (3, 2));
+        assert_eq!(turned.to_string(), "ad\nbe\ncf");
+        turned.transpose_in_place();
+        turned.rotate_left_in_place();
+        turned.rotate_right_in_place();
+        turned.rotate_bottom_in_place();
+        turned.rotate_bottom_in_place();
+        assert_eq!(turned, grid);
+
+        for d in D4::ALL {
+            let rows: Vec<&[u8]> = grid.rows().collect();
+            assert_eq!(grid.transform(d), d.apply(&rows).into());
+        }
+        assert_eq!(grid.transform(D4::TRANSPOSE).to_string(), "ad\nbe\ncf");
+
+        grid.flip();
+        assert_eq!(grid.to_string(), "cba\nfed");
+
+        let from_rows = Grid::from(vec![vec!['x', 'y'], vec!['z', 'w']]);
+        assert_eq!(from_rows.to_string(), "xy\nzw");
+    }
+}
@@ -0,0 +1,303 @@
+use crate::common::direction::ParseDirectionError;
+use std::{
+    ops::{Add, AddAssign, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// A hex in axial coordinates. The third cube coordinate is `s = -q - r`.
+///
+/// Both layouts share the same coordinates, [`PointyDir`] moves along rows of pointy-topped hexes
+/// (`e, se, sw, w, nw, ne`) and [`FlatDir`] along columns of flat-topped ones
+/// (`n, ne, se, s, sw, nw`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex {
+    pub q: isize,
+    pub r: isize,
+}
+
+impl Hex {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    /// The six neighbor offsets, starting east and going clockwise for pointy-topped hexes.
+    pub const DELTAS: [Self; 6] = [
+        Self::new(1, 0),
+        Self::new(0, 1),
+        Self::new(-1, 1),
+        Self::new(-1, 0),
+        Self::new(0, -1),
+        Self::new(1, -1),
+    ];
+
+    pub const fn new(q: isize, r: isize) -> Self {
+        Self { q, r }
+    }
+
+    /// Builds a hex from cube coordinates, returning `None` unless `q + r + s = 0`.
+    pub fn from_cube(q: isize, r: isize, s: isize) -> Option<Self> {
+        (q + r + s == 0).then_some(Self::new(q, r))
+    }
+
+    pub fn s(self) -> isize {
+        -self.q - self.r
+    }
+
+    pub fn cube(self) -> (isize, isize, isize) {
+        (self.q, self.r, self.s())
+    }
+
+    /// Number of steps between two hexes.
+    pub fn distance(self, other: Self) -> usize {
+        let d = self - other;
+        (d.q.unsigned_abs() + d.r.unsigned_abs() + d.s().unsigned_abs()) / 2
+    }
+
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        Self::DELTAS.into_iter().map(move |d| self + d)
+    }
+
+    /// Maps every hex within `radius` steps of the origin to a unique index below
+    /// `(2 * radius + 1)^2`, for use as a `mapper` with
+    /// [`bfs_count_bitset`](crate::common::pathfinding::bfs_count_bitset).
+    pub fn index(self, radius: usize) -> usize {
+        let radius = radius as isize;
+        let side = 2 * radius + 1;
+        debug_assert!(self.q.abs() <= radius && self.r.abs() <= radius);
+        ((self.r + radius) * side + self.q + radius) as usize
+    }
+
+    /// Inverse of [`Hex::index`].
+    pub fn from_index(index: usize, radius: usize) -> Self {
+        let side = 2 * radius + 1;
+        let radius = radius as isize;
+        Self::new(
+            (index % side) as isize - radius,
+            (index / side) as isize - radius,
+        )
+    }
+}
+
+impl Add for Hex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Mul<isize> for Hex {
+    type Output = Self;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Self::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.q, -self.r)
+    }
+}
+
+/// Directions between pointy-topped hexes, which are laid out in rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PointyDir {
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+    NE,
+}
+
+impl PointyDir {
+    /// Every direction, clockwise from east.
+    pub const ALL: [Self; 6] = [Self::E, Self::SE, Self::SW, Self::W, Self::NW, Self::NE];
+
+    pub fn delta(self) -> Hex {
+        Hex::DELTAS[self as usize]
+    }
+}
+
+impl FromStr for PointyDir {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "e" => Ok(Self::E),
+            "se" => Ok(Self::SE),
+            "sw" => Ok(Self::SW),
+            "w" => Ok(Self::W),
+            "nw" => Ok(Self::NW),
+            "ne" => Ok(Self::NE),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+/// Directions between flat-topped hexes, which are laid out in columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FlatDir {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
+}
+
+impl FlatDir {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Self; 6] = [Self::N, Self::NE, Self::SE, Self::S, Self::SW, Self::NW];
+
+    pub fn delta(self) -> Hex {
+        match self {
+            Self::N => Hex::new(0, -1),
+            Self::NE => Hex::new(1, -1),
+            Self::SE => Hex::new(1, 0),
+            Self::S => Hex::new(0, 1),
+            Self::SW => Hex::new(-1, 1),
+            Self::NW => Hex::new(-1, 0),
+        }
+    }
+}
+
+impl FromStr for FlatDir {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "n" => Ok(Self::N),
+            "ne" => Ok(Self::NE),
+            "se" => Ok(Self::SE),
+            "s" => Ok(Self::S),
+            "sw" => Ok(Self::SW),
+            "nw" => Ok(Self::NW),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+/// Parses a list of hex directions, either separated by commas or whitespace (`ne,ne,s`) or
+/// written back to back (`esenee`). Two letter directions are preferred over one letter ones.
+pub fn parse_hex_path<D>(s: &str) -> Result<Vec<D>, ParseDirectionError>
+where
+    D: FromStr<Err = ParseDirectionError>,
+{
+    let mut path = Vec::new();
+    let mut rest = s.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+
+    while !rest.is_empty() {
+        let (dir, len) = match rest.get(..2).map(str::parse) {
+            Some(Ok(dir)) => (dir, 2),
+            _ => (rest.get(..1).ok_or(ParseDirectionError)?.parse()?, 1),
+        };
+        path.push(dir);
+        rest = rest[len..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+
+    Ok(path)
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::pathfinding::bfs_count_bitset;
+
+    fn walk_flat(path: &str) -> Hex {
+        parse_hex_path::<FlatDir>(path)
+            .unwrap()
+            .into_iter()
+            .fold(Hex::ORIGIN, |h, d| h + d.delta())
+    }
+
+    #[test]
+    fn test_flat_paths() {
+        assert_eq!(walk_flat("ne,ne,ne").distance(Hex::ORIGIN), 3);
+        assert_eq!(walk_flat("ne,ne,sw,sw").distance(Hex::ORIGIN), 0);
+        assert_eq!(walk_flat("ne,ne,s,s").distance(Hex::ORIGIN), 2);
+        assert_eq!(walk_flat("se,sw,se,sw,sw").distance(Hex::ORIGIN), 3);
+        assert_eq!(walk_flat("n,s\n"), Hex::ORIGIN);
+        assert_eq!(parse_hex_path::<FlatDir>("n,e"), Err(ParseDirectionError));
+    }
+
+    #[test]
+    fn test_pointy_paths() {
+        let walk = |path: &str| {
+            parse_hex_path::<PointyDir>(path)
+                .unwrap()
+                .into_iter()
+                .fold(Hex::ORIGIN, |h, d| h + d.delta())
+        };
+
+        assert_eq!(
+            parse_hex_path::<PointyDir>("esenee").unwrap(),
+            vec![PointyDir::E, PointyDir::SE, PointyDir::NE, PointyDir::E]
+        );
+        assert_eq!(walk("esew"), PointyDir::SE.delta());
+        assert_eq!(walk("nwwswee"), Hex::ORIGIN);
+        assert_eq!(parse_hex_path::<PointyDir>("n"), Err(ParseDirectionError));
+    }
+
+    #[test]
+    fn test_hex() {
+        let h = Hex::new(2, -3);
+        assert_eq!(h.cube(), (2, -3, 1));
+        assert_eq!(Hex::from_cube(2, -3, 1), Some(h));
+        assert_eq!(Hex::from_cube(2, -3, 0), None);
+        assert_eq!(h.distance(Hex::ORIGIN), 3);
+        assert_eq!((h * 2).distance(-h), 9);
+
+        assert!(h.neighbors().all(|n| n.distance(h) == 1));
+        for (p, f) in PointyDir::ALL.into_iter().zip(FlatDir::ALL) {
+            assert_eq!(p.delta().distance(Hex::ORIGIN), 1);
+            assert_eq!(f.delta().distance(Hex::ORIGIN), 1);
+        }
+    }
+
+    #[test]
+    fn test_index() {
+        let radius = 4;
+        let mut seen = std::collections::HashSet::new();
+        for q in -4..=4 {
+            for r in -4..=4 {
+                let h = Hex::new(q, r);
+                if h.distance(Hex::ORIGIN) <= radius {
+                    let i = h.index(radius);
+                    assert!(i < 81);
+                    assert!(seen.insert(i));
+                    assert_eq!(Hex::from_index(i, radius), h);
+                }
+            }
+        }
+
+        let target = Hex::new(3, -1);
+        let steps = bfs_count_bitset(
+            &Hex::ORIGIN,
+            |h| {
+                h.neighbors()
+                    .filter(|n| n.distance(Hex::ORIGIN) <= radius)
+                    .collect::<Vec<_>>()
+            },
+            |&h| h == target,
+            |h| h.index(radius),
+        );
+        assert_eq!(steps, Some(3));
+    }
+}
@@ -1,42 +1,54 @@
+use crate::common::GetMutTwice;
+use bit_set::BitSet;
+
+pub mod d4;
+pub mod square;
+pub mod view;
+
+fn swap_cells<T, A>(a: &mut [A], (r0, c0): (usize, usize), (r1, c1): (usize, usize))
+where
+    A: AsMut<[T]>,
+{
+    if r0 == r1 {
+        a[r0].as_mut().swap(c0, c1);
+    } else {
+        let (row0, row1) = a.get_mut_twice(r0, r1);
+        std::mem::swap(&mut row0.as_mut()[c0], &mut row1.as_mut()[c1]);
+    }
+}
+
 pub fn rotate_right<T, A>(a: &mut [A])
 where
-    T: Default + Copy,
     A: AsMut<[T]>,
 {
     assert_eq!(a.len(), a[0].as_mut().len(), "not a square matrix");
     let len = a.len();
     for i in 0..(len / 2) {
         for j in i..(len - i - 1) {
-            let temp = a[i].as_mut()[j];
-            a[i].as_mut()[j] = a[len - 1 - j].as_mut()[i];
-            a[len - 1 - j].as_mut()[i] = a[len - 1 - i].as_mut()[len - 1 - j];
-            a[len - 1 - i].as_mut()[len - 1 - j] = a[j].as_mut()[len - 1 - i];
-            a[j].as_mut()[len - 1 - i] = temp;
+            swap_cells(a, (i, j), (len - 1 - j, i));
+            swap_cells(a, (len - 1 - j, i), (len - 1 - i, len - 1 - j));
+            swap_cells(a, (len - 1 - i, len - 1 - j), (j, len - 1 - i));
         }
     }
 }
 
 pub fn rotate_left<T, A>(a: &mut [A])
 where
-    T: Default + Copy,
     A: AsMut<[T]>,
 {
     assert_eq!(a.len(), a[0].as_mut().len(), "not a square matrix");
     let len = a.len();
     for i in 0..(len / 2) {
         for j in i..(len - i - 1) {
-            let temp = a[i].as_mut()[j];
-            a[i].as_mut()[j] = a[j].as_mut()[len - 1 - i];
-            a[j].as_mut()[len - 1 - i] = a[len - 1 - i].as_mut()[len - 1 - j];
-            a[len - 1 - i].as_mut()[len - 1 - j] = a[len - 1 - j].as_mut()[i];
-            a[len - 1 - j].as_mut()[i] = temp;
+            swap_cells(a, (i, j), (j, len - 1 - i));
+            swap_cells(a, (j, len - 1 - i), (len - 1 - i, len - 1 - j));
+            swap_cells(a, (len - 1 - i, len - 1 - j), (len - 1 - j, i));
         }
     }
 }
 
 pub fn rotate_bottom<T, A>(a: &mut [A])
 where
-    T: Default + Copy,
     A: AsMut<[T]>,
 {
     assert_eq!(a.len(), a[0].as_mut().len(), "not a square matrix");
@@ -50,16 +62,13 @@ where
 
     for i in 0..(len / 2) {
         for j in 0..len {
-            let temp = a[i].as_mut()[j];
-            a[i].as_mut()[j] = a[len - i - 1].as_mut()[len - j - 1];
-            a[len - i - 1].as_mut()[len - j - 1] = temp;
+            swap_cells(a, (i, j), (len - i - 1, len - j - 1));
         }
     }
 }
 
 pub fn flip<T, A>(a: &mut [A])
 where
-    T: Default + Copy,
     A: AsMut<[T]>,
 {
     assert_eq!(a.len(), a[0].as_mut().len(), "not a square matrix");
@@ -70,56 +79,90 @@ where
 
 pub fn rotate_right_m_n<T, A>(a: &[A]) -> Vec<Vec<T>>
 where
-    T: Default + Copy,
+    T: Clone,
     A: AsRef<[T]>,
 {
     let rows = a.len();
     let cols = a[0].as_ref().len();
 
-    let mut rotated: Vec<Vec<T>> = vec![vec![T::default(); rows]; cols];
-    for i in 0..rows {
-        for (j, &v) in a[rows - 1 - i].as_ref().iter().enumerate() {
-            rotated[j][i] = v;
-        }
-    }
-
-    rotated
+    (0..cols)
+        .map(|j| (0..rows).rev().map(|i| a[i].as_ref()[j].clone()).collect())
+        .collect()
 }
 
 pub fn rotate_left_m_n<T, A>(a: &[A]) -> Vec<Vec<T>>
 where
-    T: Default + Copy,
+    T: Clone,
     A: AsRef<[T]>,
 {
-    let rows = a.len();
     let cols = a[0].as_ref().len();
 
-    let mut rotated = vec![vec![T::default(); rows]; cols];
-    for (i, row) in a.iter().enumerate() {
-        for j in 0..cols {
-            rotated[cols - 1 - j][i] = row.as_ref()[j];
-        }
-    }
-
-    rotated
+    (0..cols)
+        .rev()
+        .map(|j| a.iter().map(|row| row.as_ref()[j].clone()).collect())
+        .collect()
 }
 
 pub fn rotate_bottom_m_n<T, A>(a: &[A]) -> Vec<Vec<T>>
 where
-    T: Default + Copy,
+    T: Clone,
     A: AsRef<[T]>,
 {
-    let rows = a.len();
-    let cols = a[0].as_ref().len();
+    a.iter()
+        .rev()
+        .map(|row| row.as_ref().iter().rev().cloned().collect())
+        .collect()
+}
 
-    let mut rotated = vec![vec![T::default(); cols]; rows];
-    for i in 0..rows {
-        for j in 0..cols {
-            rotated[rows - 1 - i][cols - 1 - j] = a[i].as_ref()[j];
+/// Moves the element at every index `i` to `target(i)` by following the cycles of the
+/// permutation, swapping instead of copying. Uses one bit of bookkeeping per element.
+fn permute_in_place<T>(data: &mut [T], target: impl Fn(usize) -> usize) {
+    let mut done = BitSet::with_capacity(data.len());
+    for start in 0..data.len() {
+        if done.contains(start) {
+            continue;
+        }
+        done.insert(start);
+
+        // start always holds the element that belongs at `next`
+        let mut next = target(start);
+        while next != start {
+            data.swap(start, next);
+            done.insert(next);
+            next = target(next);
         }
     }
+}
+
+/// Transposes a row-major `width x height` buffer in place. Returns the new `(width, height)`.
+pub fn transpose_flat<T>(data: &mut [T], width: usize, height: usize) -> (usize, usize) {
+    assert_eq!(data.len(), width * height, "data doesn't match dimensions");
+    permute_in_place(data, |i| (i % width) * height + i / width);
+    (height, width)
+}
 
-    rotated
+/// Rotates a row-major `width x height` buffer 90° clockwise in place. Returns the new
+/// `(width, height)`.
+pub fn rotate_right_flat<T>(data: &mut [T], width: usize, height: usize) -> (usize, usize) {
+    assert_eq!(data.len(), width * height, "data doesn't match dimensions");
+    permute_in_place(data, |i| (i % width) * height + (height - 1 - i / width));
+    (height, width)
+}
+
+/// Rotates a row-major `width x height` buffer 90° counter-clockwise in place. Returns the new
+/// `(width, height)`.
+pub fn rotate_left_flat<T>(data: &mut [T], width: usize, height: usize) -> (usize, usize) {
+    assert_eq!(data.len(), width * height, "data doesn't match dimensions");
+    permute_in_place(data, |i| (width - 1 - i % width) * height + i / width);
+    (height, width)
+}
+
+/// Rotates a row-major `width x height` buffer 180° in place. Returns the unchanged
+/// `(width, height)`.
+pub fn rotate_bottom_flat<T>(data: &mut [T], width: usize, height: usize) -> (usize, usize) {
+    assert_eq!(data.len(), width * height, "data doesn't match dimensions");
+    data.reverse();
+    (width, height)
 }
 
 #[cfg(feature = "common_test")]
@@ -234,4 +277,59 @@ mod tests {
             vec![3, 2, 1],
         ]);
     }
+
+    #[test]
+    fn test_flat_rotations() {
+        let mat = [[1, 2, 3], [4, 5, 6]];
+        let flat = |m: Vec<Vec<i32>>| m.concat();
+
+        let mut data = mat.concat();
+        assert_eq!(transpose_flat(&mut data, 3, 2), (2, 3));
+        assert_eq!(data, vec![1, 4, 2, 5, 3, 6]);
+
+        let mut data = mat.concat();
+        assert_eq!(rotate_right_flat(&mut data, 3, 2), (2, 3));
+        assert_eq!(data, flat(rotate_right_m_n(&mat)));
+
+        let mut data = mat.concat();
+        assert_eq!(rotate_left_flat(&mut data, 3, 2), (2, 3));
+        assert_eq!(data, flat(rotate_left_m_n(&mat)));
+
+        let mut data = mat.concat();
+        assert_eq!(rotate_bottom_flat(&mut data, 3, 2), (3, 2));
+        assert_eq!(data, flat(rotate_bottom_m_n(&mat)));
+
+        // four turns of a larger non-Copy buffer are the identity
+        let original: Vec<String> = (0..35).map(|i| i.to_string()).collect();
+        let mut data = original.clone();
+        let (mut w, mut h) = (7, 5);
+        for _ in 0..4 {
+            (w, h) = rotate_right_flat(&mut data, w, h);
+        }
+        assert_eq!((w, h), (7, 5));
+        assert_eq!(data, original);
+
+        (w, h) = transpose_flat(&mut data, w, h);
+        assert_eq!(data[1], "7");
+        transpose_flat(&mut data, w, h);
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn test_square_rotations_non_copy() {
+        let mut mat: Vec<Vec<String>> = (0..4)
+            .map(|r| (0..4).map(|c| format!("{r}{c}")).collect())
+            .collect();
+        let rotated = rotate_right_m_n(&mat);
+        rotate_right(&mut mat);
+        assert_eq!(mat, rotated);
+
+        let rotated = rotate_bottom_m_n(&mat);
+        rotate_bottom(&mut mat);
+        assert_eq!(mat, rotated);
+
+        let rotated = rotate_left_m_n(&mat);
+        rotate_left(&mut mat);
+        assert_eq!(mat, rotated);
+    }
 }
@@ -0,0 +1,234 @@
+/// One of the 8 symmetries of a square (the dihedral group D4): an optional left-right mirror
+/// followed by `rotation` quarter turns clockwise.
+///
+/// Coordinates are `(row, column)` in a grid of `(height, width)`, so rectangular grids work too,
+/// odd rotations just swap the dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct D4 {
+    rotation: u8,
+    flip: bool,
+}
+
+impl D4 {
+    pub const IDENTITY: Self = Self::new(0, false);
+    pub const ROTATE_RIGHT: Self = Self::new(1, false);
+    pub const ROTATE_BOTTOM: Self = Self::new(2, false);
+    pub const ROTATE_LEFT: Self = Self::new(3, false);
+    /// Mirrors left to right, like [`flip`](super::flip).
+    pub const FLIP: Self = Self::new(0, true);
+    /// Mirrors top to bottom.
+    pub const FLIP_VERTICAL: Self = Self::new(2, true);
+    /// Swaps rows and columns.
+    pub const TRANSPOSE: Self = Self::new(3, true);
+    /// Mirrors along the anti-diagonal.
+    pub const ANTI_TRANSPOSE: Self = Self::new(1, true);
+
+    pub const ALL: [Self; 8] = [
+        Self::new(0, false),
+        Self::new(1, false),
+        Self::new(2, false),
+        Self::new(3, false),
+        Self::new(0, true),
+        Self::new(1, true),
+        Self::new(2, true),
+        Self::new(3, true),
+    ];
+
+    pub const fn new(rotation: u8, flip: bool) -> Self {
+        Self {
+            rotation: rotation % 4,
+            flip,
+        }
+    }
+
+    /// Number of clockwise quarter turns, applied after the mirror.
+    pub fn rotation(self) -> u8 {
+        self.rotation
+    }
+
+    pub fn is_flipped(self) -> bool {
+        self.flip
+    }
+
+    /// Applies `self` and then `other`.
+    pub fn then(self, other: Self) -> Self {
+        // mirroring reverses the direction of rotations applied before it
+        let rotation = if other.flip {
+            other.rotation + 4 - self.rotation
+        } else {
+            other.rotation + self.rotation
+        };
+        Self::new(rotation, self.flip != other.flip)
+    }
+
+    /// Returns `self ∘ other`, applying `other` first.
+    pub fn compose(self, other: Self) -> Self {
+        other.then(self)
+    }
+
+    pub fn inverse(self) -> Self {
+        if self.flip {
+            self
+        } else {
+            Self::new(4 - self.rotation, false)
+        }
+    }
+
+    /// Dimensions `(height, width)` of a grid of `(height, width)` after the transform.
+    pub fn dims(self, (height, width): (usize, usize)) -> (usize, usize) {
+        if self.rotation.is_multiple_of(2) {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Where `(r, c)` in a grid of `(height, width)` ends up after the transform.
+    pub fn transform(
+        self,
+        (r, c): (usize, usize),
+        (height, width): (usize, usize),
+    ) -> (usize, usize) {
+        let (mut r, mut c) = (r, c);
+        let (mut height, mut width) = (height, width);
+        if self.flip {
+            c = width - 1 - c;
+        }
+        for _ in 0..self.rotation {
+            (r, c) = (c, height - 1 - r);
+            (height, width) = (width, height);
+        }
+        (r, c)
+    }
+
+    /// Transforms a signed vector around the origin, with rows growing downwards. Useful for
+    /// direction deltas, e.g. [`D4::ROTATE_RIGHT`] turns north `(-1, 0)` into east `(0, 1)`.
+    pub fn transform_delta(self, (r, c): (isize, isize)) -> (isize, isize) {
+        let (mut r, mut c) = (r, if self.flip { -c } else { c });
+        for _ in 0..self.rotation {
+            (r, c) = (c, -r);
+        }
+        (r, c)
+    }
+
+    /// Transforms a rectangular grid into a new one.
+    pub fn apply<T, A>(self, a: &[A]) -> Vec<Vec<T>>
+    where
+        T: Clone,
+        A: AsRef<[T]>,
+    {
+        let height = a.len();
+        let width = a.first().map_or(0, |row| row.as_ref().len());
+        let (new_height, new_width) = self.dims((height, width));
+
+        // walk the output and look up where each cell came from
+        let inverse = self.inverse();
+        (0..new_height)
+            .map(|r| {
+                (0..new_width)
+                    .map(|c| {
+                        let (sr, sc) = inverse.transform((r, c), (new_height, new_width));
+                        a[sr].as_ref()[sc].clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Every orientation of a tile, each with the transform that produced it.
+pub fn orientations<T, A>(a: &[A]) -> impl Iterator<Item = (D4, Vec<Vec<T>>)> + '_
+where
+    T: Clone,
+    A: AsRef<[T]>,
+{
+    D4::ALL.into_iter().map(move |d| (d, d.apply(a)))
+}
+
+/// The smallest of all orientations of a tile, so two tiles are the same up to rotation and
+/// flipping exactly when their canonical forms are equal. Also returns the transform that maps
+/// `a` to it.
+pub fn canonical<T, A>(a: &[A]) -> (D4, Vec<Vec<T>>)
+where
+    T: Clone + Ord,
+    A: AsRef<[T]>,
+{
+    orientations(a).min_by(|(_, x), (_, y)| x.cmp(y)).unwrap()
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::matrix::{flip, rotate_bottom_m_n, rotate_left_m_n, rotate_right_m_n};
+
+    #[test]
+    fn test_group() {
+        let dims = (3, 5);
+        for a in D4::ALL {
+            assert_eq!(a.then(a.inverse()), D4::IDENTITY);
+            assert_eq!(a.compose(D4::IDENTITY), a);
+            for b in D4::ALL {
+                let ab = a.then(b);
+                assert_eq!(ab, b.compose(a));
+                for p in [(0, 0), (1, 4), (2, 3)] {
+                    let step = b.transform(a.transform(p, dims), a.dims(dims));
+                    assert_eq!(ab.transform(p, dims), step);
+                }
+                assert_eq!(
+                    ab.transform_delta((2, -1)),
+                    b.transform_delta(a.transform_delta((2, -1)))
+                );
+            }
+        }
+
+        assert_eq!(D4::ROTATE_RIGHT.then(D4::ROTATE_RIGHT), D4::ROTATE_BOTTOM);
+        assert_eq!(D4::FLIP.then(D4::ROTATE_BOTTOM), D4::FLIP_VERTICAL);
+        assert_eq!(D4::TRANSPOSE.transform((1, 4), dims), (4, 1));
+        assert_eq!(D4::ROTATE_RIGHT.transform_delta((-1, 0)), (0, 1));
+    }
+
+    #[test]
+    fn test_apply() {
+        let a = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(D4::IDENTITY.apply(&a), a);
+        assert_eq!(D4::ROTATE_RIGHT.apply(&a), rotate_right_m_n(&a));
+        assert_eq!(D4::ROTATE_LEFT.apply(&a), rotate_left_m_n(&a));
+        assert_eq!(D4::ROTATE_BOTTOM.apply(&a), rotate_bottom_m_n(&a));
+        assert_eq!(
+            D4::TRANSPOSE.apply(&a),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(
+            D4::FLIP_VERTICAL.apply(&a),
+            vec![vec![4, 5, 6], vec![1, 2, 3]]
+        );
+
+        let mut square = [[1, 2], [3, 4]];
+        let flipped = D4::FLIP.apply(&square);
+        flip(&mut square);
+        assert_eq!(flipped, square);
+
+        for d in D4::ALL {
+            assert_eq!(d.inverse().apply(&d.apply(&a)), a);
+        }
+    }
+
+    #[test]
+    fn test_orientations() {
+        let glider = [".#.".as_bytes(), b"..#", b"###"];
+        let all: Vec<_> = orientations(&glider).map(|(_, t)| t).collect();
+        assert_eq!(all.len(), 8);
+
+        let (d, canon) = canonical(&glider);
+        assert_eq!(d.apply(&glider), canon);
+        for tile in &all {
+            assert_eq!(canonical(tile).1, canon);
+        }
+
+        let other = [b"#..".as_slice(), b"#.#", b"##."];
+        assert_eq!(canonical(&other).1, canon);
+        let different = [b"###".as_slice(), b"...", b"###"];
+        assert_ne!(canonical(&different).1, canon);
+    }
+}
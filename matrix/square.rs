@@ -0,0 +1,264 @@
+use crate::common::utils::{MyInteger, add_mod};
+use std::ops::Mul;
+
+/// An `N x N` matrix stored row-major on the stack.
+///
+/// Raising it to a power by squaring evaluates linear recurrences and "repeat this linear step
+/// 10^12 times" simulations in `O(N^3 log n)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SquareMatrix<T, const N: usize>(pub [[T; N]; N]);
+
+impl<T, const N: usize> SquareMatrix<T, N>
+where
+    T: MyInteger,
+{
+    pub fn zero() -> Self {
+        Self(std::array::from_fn(|_| std::array::from_fn(|_| T::zero())))
+    }
+
+    pub fn identity() -> Self {
+        Self(std::array::from_fn(|r| {
+            std::array::from_fn(|c| if r == c { T::one() } else { T::zero() })
+        }))
+    }
+
+    /// Companion matrix of the recurrence
+    /// `a(n) = coeffs[0] * a(n - 1) + ... + coeffs[N - 1] * a(n - N)`.
+    ///
+    /// Multiplying it with the state `[a(n - 1), ..., a(n - N)]` gives `[a(n), ..., a(n - N + 1)]`.
+    pub fn companion(coeffs: [T; N]) -> Self {
+        let mut m = Self::zero();
+        m.0[0] = coeffs;
+        for i in 1..N {
+            m.0[i][i - 1] = T::one();
+        }
+        m
+    }
+
+    fn mul_by(&self, rhs: &Self, modulus: Option<&T>) -> Self {
+        let mut result = Self::zero();
+
+        for (r, row) in result.0.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                *cell = (0..N).fold(T::zero(), |acc, k| {
+                    let a = &self.0[r][k];
+                    let b = &rhs.0[k][c];
+                    match modulus {
+                        Some(m) => add_mod(acc, a.mul_mod(b, m).mod_floor(m), m),
+                        None => acc + a.clone() * b,
+                    }
+                });
+            }
+        }
+
+        result
+    }
+
+    fn mul_vec_by(&self, v: &[T; N], modulus: Option<&T>) -> [T; N] {
+        std::array::from_fn(|r| {
+            self.0[r]
+                .iter()
+                .zip(v)
+                .fold(T::zero(), |acc, (a, b)| match modulus {
+                    Some(m) => add_mod(acc, a.mul_mod(b, m).mod_floor(m), m),
+                    None => acc + a.clone() * b,
+                })
+        })
+    }
+
+    fn pow_by(&self, mut exp: u64, modulus: Option<&T>) -> Self {
+        let mut result = Self::identity();
+        let mut base = self.clone();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul_by(&base, modulus);
+            }
+            exp >>= 1;
+            // skip the last squaring, it could overflow without a modulus
+            if exp > 0 {
+                base = base.mul_by(&base, modulus);
+            }
+        }
+
+        result
+    }
+
+    /// Matrix product with every entry reduced into `0..modulus`.
+    pub fn mul_mod(&self, rhs: &Self, modulus: &T) -> Self {
+        self.mul_by(rhs, Some(modulus))
+    }
+
+    /// Multiplies the matrix with the column vector `v`.
+    pub fn mul_vec(&self, v: &[T; N]) -> [T; N] {
+        self.mul_vec_by(v, None)
+    }
+
+    pub fn mul_vec_mod(&self, v: &[T; N], modulus: &T) -> [T; N] {
+        self.mul_vec_by(v, Some(modulus))
+    }
+
+    /// Raises the matrix to the power `exp` by squaring.
+    pub fn pow(&self, exp: u64) -> Self {
+        self.pow_by(exp, None)
+    }
+
+    /// Raises the matrix to the power `exp` with every entry reduced into `0..modulus`. Entries are
+    /// multiplied using [`MulMod`](crate::common::utils::MulMod), so moduli up to the limit of `T`
+    /// are safe.
+    pub fn pow_mod(&self, exp: u64, modulus: &T) -> Self {
+        self.pow_by(exp, Some(modulus))
+    }
+}
+
+impl<T, const N: usize> Mul for &SquareMatrix<T, N>
+where
+    T: MyInteger,
+{
+    type Output = SquareMatrix<T, N>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.mul_by(rhs, None)
+    }
+}
+
+impl<T, const N: usize> Mul for SquareMatrix<T, N>
+where
+    T: MyInteger,
+{
+    type Output = SquareMatrix<T, N>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.mul_by(&rhs, None)
+    }
+}
+
+fn recurrence_by<T, const N: usize>(
+    coeffs: [T; N],
+    initial: [T; N],
+    n: u64,
+    modulus: Option<&T>,
+) -> T
+where
+    T: MyInteger,
+{
+    if n < N as u64 {
+        let term = initial[n as usize].clone();
+        return match modulus {
+            Some(m) => term.mod_floor(m),
+            None => term,
+        };
+    }
+
+    // state is [a(N - 1), ..., a(0)]
+    let mut state = initial;
+    state.reverse();
+
+    let m = SquareMatrix::companion(coeffs).pow_by(n - (N as u64 - 1), modulus);
+    m.mul_vec_by(&state, modulus).into_iter().next().unwrap()
+}
+
+/// Evaluates the `n`th term (starting at 0) of the linear recurrence
+/// `a(n) = coeffs[0] * a(n - 1) + ... + coeffs[N - 1] * a(n - N)`, with `initial` being
+/// `[a(0), ..., a(N - 1)]`.
+pub fn linear_recurrence<T, const N: usize>(coeffs: [T; N], initial: [T; N], n: u64) -> T
+where
+    T: MyInteger,
+{
+    recurrence_by(coeffs, initial, n, None)
+}
+
+/// Same as [`linear_recurrence`], with the result reduced into `0..modulus`, even for negative
+/// coefficients or terms.
+pub fn linear_recurrence_mod<T, const N: usize>(
+    coeffs: [T; N],
+    initial: [T; N],
+    n: u64,
+    modulus: &T,
+) -> T
+where
+    T: MyInteger,
+{
+    recurrence_by(coeffs, initial, n, Some(modulus))
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_matrix_ops() {
+        let a = SquareMatrix([[1u64, 2], [3, 4]]);
+        let b = SquareMatrix([[5, 6], [7, 8]]);
+        assert_eq!(&a * &b, SquareMatrix([[19, 22], [43, 50]]));
+        assert_eq!(a.clone() * SquareMatrix::identity(), a);
+        assert_eq!(a.pow(0), SquareMatrix::identity());
+        assert_eq!(a.pow(3), &(&a * &a) * &a);
+        assert_eq!(a.mul_mod(&b, &10), SquareMatrix([[9, 2], [3, 0]]));
+        assert_eq!(a.mul_vec(&[1, 1]), [3, 7]);
+
+        // sums of products near a modulus above 2^63 don't overflow
+        let m = u64::MAX - 58;
+        let ones = SquareMatrix([[1u64, 1], [1, 1]]);
+        let big = SquareMatrix([[m - 1; 2]; 2]);
+        assert_eq!(ones.mul_mod(&big, &m), SquareMatrix([[m - 2; 2]; 2]));
+        assert_eq!(ones.mul_vec_mod(&[m - 1, m - 1], &m), [m - 2; 2]);
+        assert_eq!(big.pow_mod(2, &m), SquareMatrix([[2; 2]; 2]));
+    }
+
+    #[test]
+    fn test_linear_recurrence() {
+        // Fibonacci
+        assert_eq!(linear_recurrence([1u64, 1], [0, 1], 0), 0);
+        assert_eq!(linear_recurrence([1u64, 1], [0, 1], 1), 1);
+        assert_eq!(linear_recurrence([1u64, 1], [0, 1], 10), 55);
+        assert_eq!(
+            linear_recurrence([1u64, 1], [0, 1], 90),
+            2_880_067_194_370_816_120
+        );
+        assert_eq!(
+            linear_recurrence_mod([1u64, 1], [0, 1], 1_000_000_000_000_000_000, &1_000_000_007),
+            209_783_453
+        );
+
+        // Tribonacci
+        assert_eq!(linear_recurrence([1i64, 1, 1], [0, 0, 1], 10), 81);
+
+        // negative coefficients still give a residue in 0..m
+        assert_eq!(linear_recurrence_mod([-1i64, 0], [1, 2], 2, &7), 5);
+        assert_eq!(linear_recurrence_mod([-1i64, 0], [1, -2], 1, &7), 5);
+        assert_eq!(linear_recurrence_mod([-3i64, 1], [0, 1], 20, &11), {
+            linear_recurrence([-3i64, 1], [0, 1], 20).rem_euclid(11)
+        });
+
+        // Large modulus doesn't overflow
+        let m = (1u64 << 63) - 25;
+        let big = linear_recurrence_mod([1u64, 1], [0, 1], 1_000_000_000_000, &m);
+        assert_eq!(
+            big,
+            linear_recurrence_mod([1u128, 1], [0, 1], 1_000_000_000_000, &(m as u128)) as u64
+        );
+    }
+
+    #[test]
+    fn test_lanternfish() {
+        // Fish timers 0..=8, each day 0 resets to 6 and spawns an 8
+        let mut transition = SquareMatrix::<u64, 9>::zero();
+        for timer in 1..9 {
+            transition.0[timer - 1][timer] = 1;
+        }
+        transition.0[6][0] = 1;
+        transition.0[8][0] = 1;
+
+        let mut fish = [0; 9];
+        for timer in [3, 4, 3, 1, 2] {
+            fish[timer] += 1;
+        }
+
+        let count = |days| transition.pow(days).mul_vec(&fish).iter().sum::<u64>();
+        assert_eq!(count(18), 26);
+        assert_eq!(count(80), 5934);
+        assert_eq!(count(256), 26_984_457_539);
+    }
+}
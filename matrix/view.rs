@@ -0,0 +1,405 @@
+use super::d4::D4;
+use crate::common::{GetMutTwice, grid::Grid};
+use std::{
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// Anything that can be read as a rectangular grid of `T` by `(row, column)`.
+pub trait GridSource<T> {
+    /// `(height, width)`
+    fn dims(&self) -> (usize, usize);
+
+    fn cell(&self, r: usize, c: usize) -> &T;
+}
+
+pub trait GridSourceMut<T>: GridSource<T> {
+    fn cell_mut(&mut self, r: usize, c: usize) -> &mut T;
+
+    fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize));
+}
+
+impl<T, A> GridSource<T> for [A]
+where
+    A: AsRef<[T]>,
+{
+    fn dims(&self) -> (usize, usize) {
+        (self.len(), self.first().map_or(0, |row| row.as_ref().len()))
+    }
+
+    fn cell(&self, r: usize, c: usize) -> &T {
+        &self[r].as_ref()[c]
+    }
+}
+
+impl<T, A> GridSourceMut<T> for [A]
+where
+    A: AsRef<[T]> + AsMut<[T]>,
+{
+    fn cell_mut(&mut self, r: usize, c: usize) -> &mut T {
+        &mut self[r].as_mut()[c]
+    }
+
+    fn swap_cells(&mut self, (ar, ac): (usize, usize), (br, bc): (usize, usize)) {
+        if ar == br {
+            self[ar].as_mut().swap(ac, bc);
+        } else {
+            let (a, b) = self.get_mut_twice(ar, br);
+            std::mem::swap(&mut a.as_mut()[ac], &mut b.as_mut()[bc]);
+        }
+    }
+}
+
+impl<T, A> GridSource<T> for Vec<A>
+where
+    A: AsRef<[T]>,
+{
+    fn dims(&self) -> (usize, usize) {
+        self.as_slice().dims()
+    }
+
+    fn cell(&self, r: usize, c: usize) -> &T {
+        self.as_slice().cell(r, c)
+    }
+}
+
+impl<T, A> GridSourceMut<T> for Vec<A>
+where
+    A: AsRef<[T]> + AsMut<[T]>,
+{
+    fn cell_mut(&mut self, r: usize, c: usize) -> &mut T {
+        self.as_mut_slice().cell_mut(r, c)
+    }
+
+    fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.as_mut_slice().swap_cells(a, b);
+    }
+}
+
+impl<T> GridSource<T> for Grid<T> {
+    fn dims(&self) -> (usize, usize) {
+        (self.height(), self.width())
+    }
+
+    fn cell(&self, r: usize, c: usize) -> &T {
+        &self[(r, c)]
+    }
+}
+
+impl<T> GridSourceMut<T> for Grid<T> {
+    fn cell_mut(&mut self, r: usize, c: usize) -> &mut T {
+        &mut self[(r, c)]
+    }
+
+    fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let (a, b) = (self.index_of(a), self.index_of(b));
+        self.as_mut_slice().swap(a, b);
+    }
+}
+
+impl<T, S> GridSource<T> for &S
+where
+    S: GridSource<T> + ?Sized,
+{
+    fn dims(&self) -> (usize, usize) {
+        (**self).dims()
+    }
+
+    fn cell(&self, r: usize, c: usize) -> &T {
+        (**self).cell(r, c)
+    }
+}
+
+impl<T, S> GridSource<T> for &mut S
+where
+    S: GridSource<T> + ?Sized,
+{
+    fn dims(&self) -> (usize, usize) {
+        (**self).dims()
+    }
+
+    fn cell(&self, r: usize, c: usize) -> &T {
+        (**self).cell(r, c)
+    }
+}
+
+impl<T, S> GridSourceMut<T> for &mut S
+where
+    S: GridSourceMut<T> + ?Sized,
+{
+    fn cell_mut(&mut self, r: usize, c: usize) -> &mut T {
+        (**self).cell_mut(r, c)
+    }
+
+    fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize)) {
+        (**self).swap_cells(a, b);
+    }
+}
+
+/// A rotated, flipped or cropped view of a grid that maps coordinates instead of copying.
+///
+/// `S` is usually a reference, e.g. `&[Vec<u8>]`, `&Grid<T>` or `&mut Grid<T>`. Views over
+/// mutable references can be written through, so a tilt written once for one direction works for
+/// all four.
+pub struct GridView<S, T> {
+    source: S,
+    transform: D4,
+    // window in source coordinates
+    origin: (usize, usize),
+    size: (usize, usize),
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<S, T> GridView<S, T>
+where
+    S: GridSource<T>,
+{
+    pub fn new(source: S) -> Self {
+        let size = source.dims();
+        Self {
+            source,
+            transform: D4::IDENTITY,
+            origin: (0, 0),
+            size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `(height, width)` of the view.
+    pub fn dims(&self) -> (usize, usize) {
+        self.transform.dims(self.size)
+    }
+
+    pub fn height(&self) -> usize {
+        self.dims().0
+    }
+
+    pub fn width(&self) -> usize {
+        self.dims().1
+    }
+
+    /// Maps a position in the view to the position in the source.
+    pub fn source_position(&self, pos: (usize, usize)) -> (usize, usize) {
+        let (r, c) = self.transform.inverse().transform(pos, self.dims());
+        (self.origin.0 + r, self.origin.1 + c)
+    }
+
+    fn contains(&self, (r, c): (usize, usize)) -> bool {
+        let (height, width) = self.dims();
+        r < height && c < width
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos).then(|| {
+            let (r, c) = self.source_position(pos);
+            self.source.cell(r, c)
+        })
+    }
+
+    /// Applies `d` on top of the current view.
+    pub fn transform(mut self, d: D4) -> Self {
+        self.transform = self.transform.then(d);
+        self
+    }
+
+    pub fn rotate_right(self) -> Self {
+        self.transform(D4::ROTATE_RIGHT)
+    }
+
+    pub fn rotate_left(self) -> Self {
+        self.transform(D4::ROTATE_LEFT)
+    }
+
+    pub fn rotate_bottom(self) -> Self {
+        self.transform(D4::ROTATE_BOTTOM)
+    }
+
+    pub fn flip(self) -> Self {
+        self.transform(D4::FLIP)
+    }
+
+    pub fn flip_vertical(self) -> Self {
+        self.transform(D4::FLIP_VERTICAL)
+    }
+
+    pub fn transpose(self) -> Self {
+        self.transform(D4::TRANSPOSE)
+    }
+
+    /// Narrows the view to `height` rows and `width` columns starting at `(r, c)`, in view
+    /// coordinates. Panics if the window doesn't fit or is empty.
+    pub fn window(mut self, (r, c): (usize, usize), (height, width): (usize, usize)) -> Self {
+        assert!(height > 0 && width > 0, "empty window");
+        let (view_height, view_width) = self.dims();
+        assert!(
+            r + height <= view_height && c + width <= view_width,
+            "window out of bounds"
+        );
+
+        let a = self.source_position((r, c));
+        let b = self.source_position((r + height - 1, c + width - 1));
+        self.origin = (a.0.min(b.0), a.1.min(b.1));
+        self.size = (a.0.abs_diff(b.0) + 1, a.1.abs_diff(b.1) + 1);
+        self
+    }
+
+    /// Cells of one row of the view.
+    pub fn row(&self, r: usize) -> impl Iterator<Item = &T> {
+        (0..self.width()).map(move |c| &self[(r, c)])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.height()).map(|r| self.row(r))
+    }
+
+    /// Every cell in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.rows().flatten()
+    }
+
+    pub fn to_vec(&self) -> Vec<Vec<T>>
+    where
+        T: Clone,
+    {
+        self.rows().map(|row| row.cloned().collect()).collect()
+    }
+}
+
+impl<S, T> GridView<S, T>
+where
+    S: GridSourceMut<T>,
+{
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            let (r, c) = self.source_position(pos);
+            Some(self.source.cell_mut(r, c))
+        } else {
+            None
+        }
+    }
+
+    /// Swaps two cells of the view, which works for any `T`.
+    pub fn swap(&mut self, a: (usize, usize), b: (usize, usize)) {
+        assert!(
+            self.contains(a) && self.contains(b),
+            "position out of bounds"
+        );
+        let (a, b) = (self.source_position(a), self.source_position(b));
+        if a != b {
+            self.source.swap_cells(a, b);
+        }
+    }
+}
+
+impl<S, T> Index<(usize, usize)> for GridView<S, T>
+where
+    S: GridSource<T>,
+{
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        self.get(pos).expect("position out of bounds")
+    }
+}
+
+impl<S, T> IndexMut<(usize, usize)> for GridView<S, T>
+where
+    S: GridSourceMut<T>,
+{
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        self.get_mut(pos).expect("position out of bounds")
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::matrix::{rotate_left_m_n, rotate_right_m_n};
+
+    #[test]
+    fn test_views() {
+        let a = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let view = GridView::new(&a);
+        assert_eq!(view.dims(), (2, 3));
+        assert_eq!(view[(1, 2)], 6);
+        assert_eq!(view.get((2, 0)), None);
+
+        assert_eq!(
+            GridView::new(&a).rotate_right().to_vec(),
+            rotate_right_m_n(&a)
+        );
+        assert_eq!(
+            GridView::new(&a).rotate_left().to_vec(),
+            rotate_left_m_n(&a)
+        );
+        for d in D4::ALL {
+            assert_eq!(GridView::new(&a).transform(d).to_vec(), d.apply(&a));
+        }
+        assert_eq!(
+            GridView::new(&a).transpose().flip().to_vec(),
+            rotate_right_m_n(&a)
+        );
+
+        let rows = ["abcd", "efgh", "ijkl"].map(str::as_bytes);
+        let window = GridView::new(&rows[..]).window((1, 1), (2, 2));
+        assert_eq!(window.to_vec(), vec![b"fg".to_vec(), b"jk".to_vec()]);
+        assert_eq!(
+            window.rotate_right().to_vec(),
+            vec![b"jf".to_vec(), b"kg".to_vec()]
+        );
+
+        // windows of a transformed view
+        let view = GridView::new(&rows[..])
+            .rotate_left()
+            .window((0, 1), (2, 2));
+        assert_eq!(view.to_vec(), vec![b"hl".to_vec(), b"gk".to_vec()]);
+        assert_eq!(view.iter().count(), 4);
+    }
+
+    fn tilt_north<S: GridSourceMut<u8>>(view: &mut GridView<S, u8>) {
+        let (height, width) = view.dims();
+        for c in 0..width {
+            let mut free = 0;
+            for r in 0..height {
+                match view[(r, c)] {
+                    b'#' => free = r + 1,
+                    b'O' => {
+                        view.swap((free, c), (r, c));
+                        free += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn load(grid: &Grid<u8>) -> usize {
+        grid.position_all(|&b| b == b'O')
+            .map(|(r, _)| grid.height() - r)
+            .sum()
+    }
+
+    #[test]
+    fn test_tilt_cycle() {
+        let input = "O....#....\nO.OO#....#\n.....##...\nOO.#O....O\n.O.....O#.\n\
+                     O.#..O.#.#\n..O..#O..O\n.......O..\n#....###..\n#OO..#....";
+        let mut grid: Grid<u8> = input.parse().unwrap();
+
+        let mut north = grid.clone();
+        tilt_north(&mut GridView::new(&mut north));
+        assert_eq!(load(&north), 136);
+
+        for d in [
+            D4::IDENTITY,
+            D4::TRANSPOSE,
+            D4::FLIP_VERTICAL,
+            D4::ROTATE_LEFT,
+        ] {
+            tilt_north(&mut GridView::new(&mut grid).transform(d));
+        }
+        let expected = ".....#....\n....#...O#\n...OO##...\n.OO#......\n.....OOO#.\n\
+                        .O#...O#.#\n....O#....\n......OOOO\n#...O###..\n#..OO#....";
+        assert_eq!(grid.to_string(), expected);
+    }
+}
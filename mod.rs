@@ -1,11 +1,20 @@
+pub mod automaton;
+pub mod bit_grid;
+pub mod cycle;
+pub mod direction;
 mod extensions;
+pub mod grid;
 pub mod heap_retain;
+pub mod hex;
 pub mod matrix;
 pub mod nom;
+pub mod ocr;
 pub mod pairing;
 pub mod parse;
 pub mod pathfinding;
+pub mod point;
 mod range;
+pub mod render;
 pub mod sstr;
 pub mod utils;
 
@@ -0,0 +1,296 @@
+use crate::common::bit_grid::BitGrid;
+use num::PrimInt;
+use std::{collections::HashSet, fmt};
+
+/// Letters 4 wide and 6 tall, one blank column apart, as drawn by most CRT and folding puzzles.
+const SMALL: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// Letters 6 wide and 10 tall, as drawn by the message in the stars.
+const LARGE: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The lit cells span a number of rows that matches no font.
+    UnknownHeight(usize),
+    /// No letter starts at this column.
+    UnknownGlyph(usize),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownHeight(height) => write!(f, "no font is {height} rows tall"),
+            Self::UnknownGlyph(column) => write!(f, "unknown glyph at column {column}"),
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Columns of a glyph as bit masks, row 0 in the lowest bit, without blank columns on the sides.
+fn glyph_columns(glyph: &str) -> Vec<u16> {
+    let rows: Vec<&[u8]> = glyph.lines().map(str::as_bytes).collect();
+    let columns: Vec<u16> = (0..rows[0].len())
+        .map(|c| {
+            rows.iter()
+                .enumerate()
+                .filter(|(_, row)| row[c] == b'#')
+                .fold(0, |mask, (r, _)| mask | 1 << r)
+        })
+        .collect();
+
+    let start = columns.iter().position(|&m| m != 0).unwrap();
+    let end = columns.iter().rposition(|&m| m != 0).unwrap();
+    columns[start..=end].to_vec()
+}
+
+/// Reads block letters from `height` rows of `width` lit or unlit cells. Blank rows around the
+/// text are ignored, letters may be separated by any number of blank columns.
+fn decode(
+    height: usize,
+    width: usize,
+    lit: impl Fn(usize, usize) -> bool,
+) -> Result<String, OcrError> {
+    let Some(top) = (0..height).find(|&r| (0..width).any(|c| lit(r, c))) else {
+        return Ok(String::new());
+    };
+    let bottom = (0..height)
+        .rfind(|&r| (0..width).any(|c| lit(r, c)))
+        .unwrap();
+
+    let font: &[(char, &str)] = match bottom - top + 1 {
+        6 => &SMALL,
+        10 => &LARGE,
+        other => return Err(OcrError::UnknownHeight(other)),
+    };
+    let mut glyphs: Vec<(char, Vec<u16>)> = font
+        .iter()
+        .map(|&(letter, glyph)| (letter, glyph_columns(glyph)))
+        .collect();
+    // try wider glyphs first so a narrower one can't match only the start of a letter
+    glyphs.sort_by_key(|(_, columns)| std::cmp::Reverse(columns.len()));
+
+    let columns: Vec<u16> = (0..width)
+        .map(|c| {
+            (top..=bottom)
+                .filter(|&r| lit(r, c))
+                .fold(0, |mask, r| mask | 1 << (r - top))
+        })
+        .collect();
+
+    let mut text = String::new();
+    let mut c = 0;
+    while c < width {
+        if columns[c] == 0 {
+            c += 1;
+            continue;
+        }
+        let (letter, glyph) = glyphs
+            .iter()
+            .find(|(_, glyph)| {
+                // a glyph must end at a blank column, otherwise it only matches part of a letter
+                columns[c..].starts_with(glyph)
+                    && columns.get(c + glyph.len()).is_none_or(|&mask| mask == 0)
+            })
+            .ok_or(OcrError::UnknownGlyph(c))?;
+        text.push(*letter);
+        c += glyph.len();
+    }
+    Ok(text)
+}
+
+/// Reads block letters from rows of cells, `true` being lit.
+pub fn ocr<A>(rows: &[A]) -> Result<String, OcrError>
+where
+    A: AsRef<[bool]>,
+{
+    let width = rows.iter().map(|row| row.as_ref().len()).max().unwrap_or(0);
+    decode(rows.len(), width, |r, c| {
+        rows[r].as_ref().get(c).copied().unwrap_or(false)
+    })
+}
+
+pub fn ocr_bit_grid(grid: &BitGrid) -> Result<String, OcrError> {
+    decode(grid.height(), grid.width(), |r, c| grid.get(r, c))
+}
+
+/// Reads block letters from the `(row, column)` positions of the lit cells. Errors report columns
+/// counted from the leftmost point.
+pub fn ocr_points<T: PrimInt>(
+    points: impl IntoIterator<Item = (T, T)>,
+) -> Result<String, OcrError> {
+    let points: HashSet<(isize, isize)> = points
+        .into_iter()
+        .map(|(r, c)| (r.to_isize().unwrap(), c.to_isize().unwrap()))
+        .collect();
+    let (Some(r_min), Some(c_min)) = (
+        points.iter().map(|p| p.0).min(),
+        points.iter().map(|p| p.1).min(),
+    ) else {
+        return Ok(String::new());
+    };
+    let height = (points.iter().map(|p| p.0).max().unwrap() - r_min + 1) as usize;
+    let width = (points.iter().map(|p| p.1).max().unwrap() - c_min + 1) as usize;
+
+    decode(height, width, |r, c| {
+        points.contains(&(r_min + r as isize, c_min + c as isize))
+    })
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_rows(s: &str) -> Vec<Vec<bool>> {
+        s.lines()
+            .map(|line| line.bytes().map(|b| b == b'#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_small_letters() {
+        // 2022 day 10
+        let crt = "\
+####..##..#....#..#.###..#....####...##.
+#....#..#.#....#..#.#..#.#.......#....#.
+###..#....#....####.###..#......#.....#.
+#....#.##.#....#..#.#..#.#.....#......#.
+#....#..#.#....#..#.#..#.#....#....#..#.
+####..###.####.#..#.###..####.####..##..";
+        assert_eq!(ocr(&to_rows(crt)).unwrap(), "EGLHBLZJ");
+
+        let bits: BitGrid = crt.parse().unwrap();
+        assert_eq!(ocr_bit_grid(&bits).unwrap(), "EGLHBLZJ");
+
+        // every letter, with Y wider than the others and I narrower
+        let text: String = SMALL.iter().map(|&(letter, _)| letter).collect();
+        let glyphs: Vec<Vec<&str>> = SMALL.iter().map(|(_, g)| g.lines().collect()).collect();
+        let rows: Vec<String> = (0..6)
+            .map(|r| glyphs.iter().map(|g| g[r]).collect::<Vec<_>>().join("."))
+            .collect();
+        assert_eq!(ocr(&to_rows(&rows.join("\n"))).unwrap(), text);
+    }
+
+    #[test]
+    fn test_large_letters() {
+        // 2018 day 10, somewhere in the sky
+        let message = "\
+#....#..#####.
+#....#..#....#
+#....#..#....#
+#....#..#....#
+######..#####.
+#....#..#.....
+#....#..#.....
+#....#..#.....
+#....#..#.....
+#....#..#.....";
+        let points = message.lines().enumerate().flat_map(|(r, line)| {
+            line.bytes()
+                .enumerate()
+                .filter(|&(_, b)| b == b'#')
+                .map(move |(c, _)| (r as i64 + 150, c as i64 - 20))
+        });
+        assert_eq!(ocr_points(points).unwrap(), "HP");
+        assert_eq!(ocr(&to_rows(message)).unwrap(), "HP");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(ocr::<Vec<bool>>(&[]), Ok(String::new()));
+        assert_eq!(
+            ocr_points([(0, 0), (2, 0)]),
+            Err(OcrError::UnknownHeight(3))
+        );
+
+        let mut rows = to_rows(".##.\n#..#\n#..#\n####\n#..#\n#..#\n....");
+        assert_eq!(ocr(&rows).unwrap(), "A");
+        rows[3][1] = false;
+        assert_eq!(ocr(&rows), Err(OcrError::UnknownGlyph(0)));
+
+        // an L with a stray lit column right after it is no L
+        let rows = to_rows("#...#\n#....\n#....\n#....\n#....\n####.");
+        assert_eq!(ocr(&rows), Err(OcrError::UnknownGlyph(0)));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::common::{
+    AddIsize,
+    direction::{Dir4, Dir8, Direction},
+    pairing::{pair2d_b, unpair2d_b},
+};
+use num::{FromPrimitive, PrimInt, Signed};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A 2D point or vector. Converts to and from `(x, y)` tuples, so when used with the `(r, c)`
+/// based grid helpers `x` is the row and `y` the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A 3D point or vector. Converts to and from `(x, y, z)` tuples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+fn abs_diff<T>(a: T, b: T) -> T
+where
+    T: Sub<Output = T> + PartialOrd,
+{
+    if a > b { a - b } else { b - a }
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    pub fn map<U, F>(self, mut f: F) -> Point2<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Point2::new(f(self.x), f(self.y))
+    }
+}
+
+impl<T> Point2<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + PartialOrd,
+{
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        if dx > dy { dx } else { dy }
+    }
+}
+
+impl<T> Point2<T>
+where
+    T: Copy + Neg<Output = T>,
+{
+    /// Rotates 90° clockwise around the origin, `(x, y) -> (y, -x)`. This turns
+    /// [`Dir4::North`]'s delta into [`Dir4::East`]'s.
+    pub fn rot90_cw(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// Rotates 90° counter-clockwise around the origin, `(x, y) -> (-y, x)`.
+    pub fn rot90_ccw(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+}
+
+impl Point2<usize> {
+    /// Adds a signed offset, returning `None` if either coordinate would go below zero.
+    pub fn checked_add_isize(self, delta: Point2<isize>) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_isize(delta.x)?,
+            self.y.checked_add_isize(delta.y)?,
+        ))
+    }
+
+    /// Like [`Point2::checked_add_isize`], but also requires the result to be below `max` in both
+    /// coordinates.
+    pub fn checked_add_isize_clamp(self, delta: Point2<isize>, max: Self) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_isize_clamp(delta.x, max.x)?,
+            self.y.checked_add_isize_clamp(delta.y, max.y)?,
+        ))
+    }
+}
+
+impl<T> Point2<T>
+where
+    T: Signed + PrimInt + FromPrimitive,
+{
+    /// Packs the point into a single `usize` with [`pair2d_b`].
+    pub fn pair(self) -> usize {
+        pair2d_b(self.x, self.y)
+    }
+
+    pub fn unpair(z: usize) -> Self {
+        unpair2d_b(z).into()
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn map<U, F>(self, mut f: F) -> Point3<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Point3::new(f(self.x), f(self.y), f(self.z))
+    }
+}
+
+impl<T> Point3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + PartialOrd,
+{
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        [abs_diff(self.y, other.y), abs_diff(self.z, other.z)]
+            .into_iter()
+            .fold(
+                abs_diff(self.x, other.x),
+                |max, d| if d > max { d } else { max },
+            )
+    }
+}
+
+impl<T> Point3<T>
+where
+    T: Copy + Neg<Output = T>,
+{
+    /// Rotates 90° around the x axis, counter-clockwise when looking from positive x towards the
+    /// origin.
+    pub fn rot90_x(self) -> Self {
+        Self::new(self.x, -self.z, self.y)
+    }
+
+    /// Rotates 90° around the y axis, counter-clockwise when looking from positive y towards the
+    /// origin.
+    pub fn rot90_y(self) -> Self {
+        Self::new(self.z, self.y, -self.x)
+    }
+
+    /// Rotates 90° around the z axis, counter-clockwise when looking from positive z towards the
+    /// origin.
+    pub fn rot90_z(self) -> Self {
+        Self::new(-self.y, self.x, self.z)
+    }
+}
+
+impl Point3<usize> {
+    /// Adds a signed offset, returning `None` if any coordinate would go below zero.
+    pub fn checked_add_isize(self, delta: Point3<isize>) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_isize(delta.x)?,
+            self.y.checked_add_isize(delta.y)?,
+            self.z.checked_add_isize(delta.z)?,
+        ))
+    }
+}
+
+macro_rules! point_ops {
+    ($point:ident, $($field:ident),+) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $point<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$field += rhs.$field;)+
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $point<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$field -= rhs.$field;)+
+            }
+        }
+    };
+}
+
+point_ops!(Point2, x, y);
+point_ops!(Point3, x, y, z);
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(p: Point2<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T> From<[T; 2]> for Point2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl<T> From<Point3<T>> for (T, T, T) {
+    fn from(p: Point3<T>) -> Self {
+        (p.x, p.y, p.z)
+    }
+}
+
+impl<T> From<[T; 3]> for Point3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl From<Dir4> for Point2<isize> {
+    fn from(dir: Dir4) -> Self {
+        dir.delta().into()
+    }
+}
+
+impl From<Dir8> for Point2<isize> {
+    fn from(dir: Dir8) -> Self {
+        dir.delta().into()
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::utils::neighbors;
+
+    #[test]
+    fn test_point2_ops() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(-3, 5);
+        assert_eq!(a + b, Point2::new(-2, 7));
+        assert_eq!(a - b, Point2::new(4, -3));
+        assert_eq!(b * 3, Point2::new(-9, 15));
+        assert_eq!(-a, Point2::new(-1, -2));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(Point2::new(3usize, 9).manhattan(&Point2::new(7, 2)), 11);
+    }
+
+    #[test]
+    fn test_point2_rotation() {
+        let north = Point2::from(Dir4::North);
+        assert_eq!(north.rot90_cw(), Point2::from(Dir4::East));
+        assert_eq!(north.rot90_ccw(), Point2::from(Dir4::West));
+
+        let p = Point2::new(2, 5);
+        assert_eq!(p.rot90_cw().rot90_ccw(), p);
+        assert_eq!(p.rot90_cw().rot90_cw(), -p);
+    }
+
+    #[test]
+    fn test_point2_conversions() {
+        let p = Point2::new(1usize, 1);
+        assert_eq!(
+            p.checked_add_isize(Dir4::North.into()),
+            Some(Point2::new(0, 1))
+        );
+        assert_eq!(p.checked_add_isize(Point2::new(0, -2)), None);
+        assert_eq!(
+            p.checked_add_isize_clamp(Dir8::SouthEast.into(), Point2::new(2, 3)),
+            None
+        );
+
+        let (r, c) = p.into();
+        let mut n: Vec<Point2<usize>> = neighbors(r, c, 3, 3).map(Point2::from).collect();
+        n.sort();
+        assert_eq!(n[0], Point2::new(0, 1));
+        assert_eq!(p.map(|v| v as isize - 2), Point2::new(-1, -1));
+
+        for p in [
+            Point2::new(0i32, 0),
+            Point2::new(-5, 3),
+            Point2::new(7, -11),
+        ] {
+            assert_eq!(Point2::unpair(p.pair()), p);
+        }
+    }
+
+    #[test]
+    fn test_point3() {
+        let a = Point3::new(1, -2, 3);
+        let b = Point3::from((4, 4, -1));
+        assert_eq!(a + b, Point3::new(5, 2, 2));
+        assert_eq!(a - b, Point3::new(-3, -6, 4));
+        assert_eq!(a * 2, Point3::new(2, -4, 6));
+        assert_eq!(a.manhattan(&b), 13);
+        assert_eq!(a.chebyshev(&b), 6);
+        assert_eq!(<(i32, i32, i32)>::from(a), (1, -2, 3));
+
+        assert_eq!(Point3::new(1, 0, 0).rot90_z(), Point3::new(0, 1, 0));
+        assert_eq!(Point3::new(0, 1, 0).rot90_x(), Point3::new(0, 0, 1));
+        assert_eq!(Point3::new(0, 0, 1).rot90_y(), Point3::new(1, 0, 0));
+        let p = Point3::new(2, 3, 5);
+        let spun = (0..4).fold(p, |p, _| p.rot90_x().rot90_y());
+        assert_eq!(spun.manhattan(&Point3::default()), 10);
+
+        assert_eq!(
+            Point3::new(0usize, 1, 2).checked_add_isize(Point3::new(0, -1, 1)),
+            Some(Point3::new(0, 0, 3))
+        );
+        assert_eq!(
+            Point3::new(0usize, 1, 2).checked_add_isize(Point3::new(-1, 0, 0)),
+            None
+        );
+    }
+}
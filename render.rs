@@ -0,0 +1,259 @@
+use crate::common::{bit_grid::BitGrid, grid::Grid};
+use num::PrimInt;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{self, Write},
+};
+
+/// Text rendering of anything grid shaped, for eyeballing puzzle state.
+///
+/// Build one from a [`Grid`], nested rows, a [`BitGrid`] or a set of points, optionally draw
+/// [`overlay`](Render::overlay) points on top (a path through a maze, the cells that changed since
+/// the last step, ...), then print it or write it out as an image.
+pub struct Render<'a> {
+    // position of the top left cell in the coordinates of the source
+    origin: (isize, isize),
+    height: usize,
+    width: usize,
+    cell: Box<dyn Fn(usize, usize) -> char + 'a>,
+    overlay: HashMap<(usize, usize), char>,
+}
+
+impl<'a> Render<'a> {
+    /// `cell(r, c)` gives the character of every position in `height` rows of `width`.
+    pub fn new(height: usize, width: usize, cell: impl Fn(usize, usize) -> char + 'a) -> Self {
+        Self {
+            origin: (0, 0),
+            height,
+            width,
+            cell: Box::new(cell),
+            overlay: HashMap::new(),
+        }
+    }
+
+    pub fn grid<T>(grid: &'a Grid<T>, mapper: impl Fn(&T) -> char + 'a) -> Self {
+        Self::new(grid.height(), grid.width(), move |r, c| {
+            mapper(&grid[(r, c)])
+        })
+    }
+
+    /// Renders a matrix of rows, like the ones in [`matrix`](crate::common::matrix).
+    pub fn rows<T, A>(a: &'a [A], mapper: impl Fn(&T) -> char + 'a) -> Self
+    where
+        A: AsRef<[T]>,
+    {
+        let width = a.first().map_or(0, |row| row.as_ref().len());
+        Self::new(a.len(), width, move |r, c| mapper(&a[r].as_ref()[c]))
+    }
+
+    /// `mapper` gets whether each cell is set.
+    pub fn bit_grid(grid: &'a BitGrid, mapper: impl Fn(bool) -> char + 'a) -> Self {
+        Self::new(grid.height(), grid.width(), move |r, c| {
+            mapper(grid.get(r, c))
+        })
+    }
+
+    /// Renders a set of `(row, column)` points cropped to their bounding box, `mapper` gets whether
+    /// each position is in the set.
+    pub fn points<T: PrimInt>(
+        points: impl IntoIterator<Item = (T, T)>,
+        mapper: impl Fn(bool) -> char + 'a,
+    ) -> Self {
+        let points: HashSet<(isize, isize)> = points
+            .into_iter()
+            .map(|(r, c)| (r.to_isize().unwrap(), c.to_isize().unwrap()))
+            .collect();
+        let (Some(r_min), Some(r_max)) = (
+            points.iter().map(|p| p.0).min(),
+            points.iter().map(|p| p.0).max(),
+        ) else {
+            return Self::new(0, 0, move |_, _| mapper(false));
+        };
+        let c_min = points.iter().map(|p| p.1).min().unwrap();
+        let c_max = points.iter().map(|p| p.1).max().unwrap();
+
+        let mut render = Self::new(
+            (r_max - r_min + 1) as usize,
+            (c_max - c_min + 1) as usize,
+            move |r, c| mapper(points.contains(&(r_min + r as isize, c_min + c as isize))),
+        );
+        render.origin = (r_min, c_min);
+        render
+    }
+
+    /// Draws `ch` over the given `(row, column)` points, in the coordinates of the source. Points
+    /// outside of the rendered area are ignored.
+    pub fn overlay<T: PrimInt>(
+        mut self,
+        points: impl IntoIterator<Item = (T, T)>,
+        ch: char,
+    ) -> Self {
+        for (r, c) in points {
+            let r = r.to_isize().unwrap() - self.origin.0;
+            let c = c.to_isize().unwrap() - self.origin.1;
+            if (0..self.height as isize).contains(&r) && (0..self.width as isize).contains(&c) {
+                self.overlay.insert((r as usize, c as usize), ch);
+            }
+        }
+        self
+    }
+
+    /// `(height, width)` of the rendered area.
+    pub fn dims(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    /// The character drawn at `(r, c)`, counted from the top left of the rendered area.
+    pub fn char_at(&self, r: usize, c: usize) -> char {
+        match self.overlay.get(&(r, c)) {
+            Some(&ch) => ch,
+            None => (self.cell)(r, c),
+        }
+    }
+
+    /// Writes a color image with one pixel per character.
+    pub fn write_ppm(&self, w: impl Write, palette: impl Fn(char) -> [u8; 3]) -> io::Result<()> {
+        write_ppm(w, self.width, self.height, |r, c| {
+            palette(self.char_at(r, c))
+        })
+    }
+
+    /// Writes a black and white image with one pixel per character, `black` picks which ones are
+    /// set.
+    pub fn write_pbm(&self, w: impl Write, black: impl Fn(char) -> bool) -> io::Result<()> {
+        write_pbm(w, self.width, self.height, |r, c| black(self.char_at(r, c)))
+    }
+}
+
+/// Rows separated by `\n`, without a trailing newline.
+impl fmt::Display for Render<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.height {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            for c in 0..self.width {
+                write!(f, "{}", self.char_at(r, c))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a binary PPM (P6) image, `pixel(r, c)` returns the RGB color of each pixel.
+pub fn write_ppm(
+    mut w: impl Write,
+    width: usize,
+    height: usize,
+    mut pixel: impl FnMut(usize, usize) -> [u8; 3],
+) -> io::Result<()> {
+    write!(w, "P6\n{width} {height}\n255\n")?;
+    let mut row = Vec::with_capacity(width * 3);
+    for r in 0..height {
+        row.clear();
+        row.extend((0..width).flat_map(|c| pixel(r, c)));
+        w.write_all(&row)?;
+    }
+    Ok(())
+}
+
+/// Writes a binary PBM (P4) image, `pixel(r, c)` returns whether a pixel is black.
+pub fn write_pbm(
+    mut w: impl Write,
+    width: usize,
+    height: usize,
+    mut pixel: impl FnMut(usize, usize) -> bool,
+) -> io::Result<()> {
+    write!(w, "P4\n{width} {height}\n")?;
+    // every row is padded to a whole number of bytes, most significant bit first
+    let mut row = vec![0u8; width.div_ceil(8)];
+    for r in 0..height {
+        row.fill(0);
+        for c in 0..width {
+            if pixel(r, c) {
+                row[c / 8] |= 0x80 >> (c % 8);
+            }
+        }
+        w.write_all(&row)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_dot(set: bool) -> char {
+        if set { '#' } else { '.' }
+    }
+
+    #[test]
+    fn test_render_text() {
+        let maze: Grid<u8> = "#####\n#...#\n#.#.#\n#####".parse().unwrap();
+        let path = [(1usize, 1usize), (1, 2), (1, 3), (2, 3)];
+        let render = Render::grid(&maze, |&b| b as char)
+            .overlay(path, 'O')
+            .overlay([(2, 1)], 'S');
+        assert_eq!(render.to_string(), "#####\n#OOO#\n#S#O#\n#####");
+        assert_eq!(render.dims(), (4, 5));
+
+        let rows = vec![vec![1, 0], vec![0, 1]];
+        let render = Render::rows(&rows, |&v| if v == 1 { '#' } else { ' ' });
+        assert_eq!(render.to_string(), "# \n #");
+
+        let bits: BitGrid = "#..\n.#.".parse().unwrap();
+        assert_eq!(
+            Render::bit_grid(&bits, hash_dot).to_string(),
+            bits.to_string()
+        );
+        let render = Render::bit_grid(&bits, |set| if set { '█' } else { ' ' });
+        assert_eq!(render.to_string(), "█  \n █ ");
+    }
+
+    #[test]
+    fn test_render_points() {
+        let points = [(-1i64, 3i64), (1, 5), (0, 4)];
+        let render = Render::points(points, hash_dot).overlay([(1, 3), (9, 9)], 'x');
+        assert_eq!(render.to_string(), "#..\n.#.\nx.#");
+        let render = Render::points(points, |set| if set { 'o' } else { ' ' });
+        assert_eq!(render.to_string(), "o  \n o \n  o");
+
+        // the cells that changed between two steps
+        let before: HashSet<_> = [(0, 0), (0, 1), (0, 2)].into();
+        let after: HashSet<_> = [(-1, 1), (0, 1), (1, 1)].into();
+        let changed = before.symmetric_difference(&after).copied();
+        let render = Render::points(after.iter().copied(), hash_dot).overlay(changed, '*');
+        assert_eq!(render.to_string(), "*\n#\n*");
+
+        assert_eq!(Render::points::<i32>([], hash_dot).to_string(), "");
+    }
+
+    #[test]
+    fn test_images() {
+        let bits: BitGrid = "#........#\n.#........".parse().unwrap();
+        let render = Render::bit_grid(&bits, hash_dot);
+
+        let mut pbm = Vec::new();
+        render.write_pbm(&mut pbm, |ch| ch == '#').unwrap();
+        assert_eq!(pbm, b"P4\n10 2\n\x80\x40\x40\x00");
+
+        let mut ppm = Vec::new();
+        Render::bit_grid(&bits, hash_dot)
+            .overlay([(1, 0)], 'r')
+            .write_ppm(&mut ppm, |ch| match ch {
+                '#' => [255, 255, 255],
+                'r' => [255, 0, 0],
+                _ => [0, 0, 0],
+            })
+            .unwrap();
+        let header = b"P6\n10 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 10 * 2 * 3);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels[..3], [255, 255, 255]);
+        assert_eq!(pixels[30..33], [255, 0, 0]);
+        assert_eq!(pixels[33..36], [255, 255, 255]);
+    }
+}
@@ -1,14 +1,32 @@
-use super::extensions::AddIsize;
+use super::{
+    direction::{Dir4, Dir8, Direction},
+    extensions::AddIsize,
+};
 use arrayvec::ArrayVec;
-use std::{fmt::Debug, ops::Mul};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::{BuildHasher, RandomState},
+    ops::Mul,
+};
 
 pub use super::parse::*;
 
+pub mod affine;
+pub mod discrete_log;
+pub mod linear_system;
+mod mul_mod;
+mod nd;
+pub mod number_theory;
 pub mod rev;
+pub mod sequence;
 
-pub trait MyInteger: num::Integer + Clone + for<'a> Mul<&'a Self, Output = Self> {}
+pub use mul_mod::{MulMod, add_mod};
+pub use nd::*;
 
-impl<T> MyInteger for T where T: num::Integer + Clone + for<'a> Mul<&'a T, Output = T> {}
+pub trait MyInteger: num::Integer + Clone + for<'a> Mul<&'a Self, Output = Self> + MulMod {}
+
+impl<T> MyInteger for T where T: num::Integer + Clone + for<'a> Mul<&'a T, Output = T> + MulMod {}
 
 // Based on the C++ algorithm here: https://stackoverflow.com/a/53604277/7263440
 #[inline]
@@ -49,6 +67,64 @@ where
     if x1.1 { m0 - x1.0 } else { x1.0 }
 }
 
+/// Fallible version of [`mod_inv`]. Returns `None` when `a` isn't invertible modulo `m`, rather
+/// than `0`. Negative residues are normalized into `[0, m)` first.
+pub fn checked_mod_inv<U>(a: U, m: U) -> Option<U>
+where
+    U: MyInteger,
+{
+    if m <= U::zero() {
+        return None;
+    }
+
+    let a = a.mod_floor(&m);
+    a.gcd(&m).is_one().then(|| mod_inv(a, m))
+}
+
+/// Extended Euclidean algorithm. Returns `(g, x, y)` such that `a * x + b * y = g`, where `g` is
+/// the non-negative gcd of `a` and `b`.
+pub fn extended_gcd<T>(a: T, b: T) -> (T, T, T)
+where
+    T: MyInteger + num::Signed,
+{
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (T::one(), T::zero());
+    let (mut y0, mut y1) = (T::zero(), T::one());
+
+    while !r1.is_zero() {
+        let q = r0.clone() / r1.clone();
+
+        let r2 = r0 - q.clone() * &r1;
+        let x2 = x0 - q.clone() * &x1;
+        let y2 = y0 - q * &y1;
+
+        (r0, r1) = (r1, r2);
+        (x0, x1) = (x1, x2);
+        (y0, y1) = (y1, y2);
+    }
+
+    if r0.is_negative() {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// Modular inverse of a signed `a` using [`extended_gcd`]. Both `a` and `m` may be negative, the
+/// result is always in `[0, |m|)`. Returns `None` when `a` isn't invertible.
+pub fn checked_mod_inv_signed<T>(a: T, m: T) -> Option<T>
+where
+    T: MyInteger + num::Signed,
+{
+    let m = m.abs();
+    if m.is_zero() {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a, m.clone());
+    g.is_one().then(|| x.mod_floor(&m))
+}
+
 pub fn mod_pow<T>(mut base: T, mut exp: T, modulus: T) -> T
 where
     T: MyInteger,
@@ -61,11 +137,11 @@ where
     base = base % modulus.clone();
     while exp > T::zero() {
         if exp.is_odd() {
-            result = result * base.clone() % modulus.clone();
+            result = result.mul_mod(&base, &modulus);
         }
 
         exp = exp / (T::one() + T::one());
-        base = base.clone() * base % modulus.clone()
+        base = base.mul_mod(&base, &modulus);
     }
     result
 }
@@ -74,21 +150,41 @@ pub fn baby_step_giant_step<I>(modulo: I, base: I, target: I) -> Option<I>
 where
     I: MyInteger + num::integer::Roots + num::ToPrimitive + std::hash::Hash,
 {
-    let m = num::integer::sqrt(modulo.clone());
+    baby_step_giant_step_bounded::<_, RandomState>(modulo.clone(), base, target, modulo)
+}
 
-    let precomp = num::range(I::zero(), m.clone())
-        .map(|j| (mod_pow(base.clone(), j.clone(), modulo.clone()), j))
-        .collect::<std::collections::HashMap<_, _>>();
+/// Baby-step giant-step that only searches exponents in `0..bound`, e.g. the order of `base`, and
+/// uses the hasher `S` for the `O(sqrt(bound))` lookup table.
+///
+/// Returns the smallest `x` in `0..bound` such that `base^x = target (mod modulo)`.
+pub fn baby_step_giant_step_bounded<I, S>(modulo: I, base: I, target: I, bound: I) -> Option<I>
+where
+    I: MyInteger + num::integer::Roots + num::ToPrimitive + std::hash::Hash,
+    S: BuildHasher + Default,
+{
+    let mut m = num::integer::sqrt(bound.clone());
+    if m.clone() * &m < bound {
+        m = m + I::one();
+    }
 
-    let invgenerator = mod_inv(mod_pow(base, m.clone(), modulo.clone()), modulo.clone());
-    let mut value = target;
+    let mut precomp = HashMap::with_capacity_and_hasher(m.to_usize().unwrap_or(0), S::default());
+    let mut power = I::one() % modulo.clone();
+    for j in num::range(I::zero(), m.clone()) {
+        // keep the smallest j, so that the first hit is the smallest exponent
+        precomp.entry(power.clone()).or_insert(j);
+        power = power.mul_mod(&base, &modulo);
+    }
+
+    let invgenerator = mod_inv(power, modulo.clone());
+    let mut value = target % modulo.clone();
 
     for i in num::range(I::zero(), m.clone()) {
         if let Some(v) = precomp.get(&value) {
-            return Some(i * m + v.clone());
+            let x = i * m.clone() + v.clone();
+            return (x < bound).then_some(x);
         }
 
-        value = value * invgenerator.clone() % modulo.clone();
+        value = value.mul_mod(&invgenerator, &modulo);
     }
 
     None
@@ -110,12 +206,54 @@ where
         let a = product.clone() / m.clone();
         let y = mod_inv(a.clone(), m.clone());
 
-        sum = sum + x * a * y;
+        sum = (sum + x.mul_mod(&a, &product).mul_mod(&y, &product)) % product.clone();
     }
 
     sum % product
 }
 
+/// Generalized CRT that doesn't require the moduli to be pairwise coprime. Congruences are merged
+/// one at a time using the gcd and lcm of the moduli.
+///
+/// Returns `(residue, lcm)` where `residue` is the smallest non-negative solution, or `None` if the
+/// system is inconsistent. An empty input returns `(0, 1)`.
+pub fn chinese_remainder_theorem_general<T, I>(inputs: I) -> Option<(T, T)>
+where
+    T: MyInteger,
+    I: IntoIterator<Item = (T, T)>,
+{
+    let mut acc = (T::zero(), T::one());
+
+    for (r2, m2) in inputs {
+        let (r1, m1) = acc;
+        let g = m1.gcd(&m2);
+
+        // (r2 - r1) mod m2, kept non-negative so unsigned types work as well, and without adding
+        // so moduli near the limit of T don't overflow
+        let r2 = r2.mod_floor(&m2);
+        let r1_m2 = r1.mod_floor(&m2);
+        let diff = if r2 >= r1_m2 {
+            r2 - r1_m2
+        } else {
+            m2.clone() - (r1_m2 - r2)
+        };
+        if !(diff.clone() % g.clone()).is_zero() {
+            return None;
+        }
+
+        let m2g = m2 / g.clone();
+        let inv = mod_inv(m1.clone() / g.clone() % m2g.clone(), m2g.clone());
+        let k = (diff / g).mul_mod(&inv, &m2g);
+        let lcm = m1.clone() * m2g;
+
+        // r1 < m1 and m1 * k < lcm, both already reduced
+        let step = m1.mul_mod(&k, &lcm);
+        acc = (add_mod(r1, step, &lcm), lcm);
+    }
+
+    Some(acc)
+}
+
 pub fn build_array<T, I, const N: usize>(iter: I) -> [T; N]
 where
     T: Debug,
@@ -127,6 +265,37 @@ where
         .unwrap()
 }
 
+/// Offsets of the 4 orthogonal neighbors as `(row, column)` deltas.
+pub const NEIGHBORS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// [`NEIGHBORS`] with `(0, 0)` first.
+pub const NEIGHBORS_AND_SELF: [(isize, isize); 5] = [(0, 0), (-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets of the 8 orthogonal and diagonal neighbors.
+pub const NEIGHBORS_DIAG: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// [`NEIGHBORS_DIAG`] with `(0, 0)` first.
+pub const NEIGHBORS_DIAG_AND_SELF: [(isize, isize); 9] = [
+    (0, 0),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
 pub fn neighbors_arbitray(
     diffs: &[(isize, isize)],
     r: usize,
@@ -148,7 +317,7 @@ pub fn neighbors(
     r_max: usize,
     c_max: usize,
 ) -> impl Iterator<Item = (usize, usize)> {
-    neighbors_arbitray(&[(-1, 0), (0, -1), (0, 1), (1, 0)], r, c, r_max, c_max)
+    neighbors_arbitray(&NEIGHBORS, r, c, r_max, c_max)
 }
 
 pub fn neighbors_and_self(
@@ -157,13 +326,7 @@ pub fn neighbors_and_self(
     r_max: usize,
     c_max: usize,
 ) -> impl Iterator<Item = (usize, usize)> {
-    neighbors_arbitray(
-        &[(0, 0), (-1, 0), (0, -1), (0, 1), (1, 0)],
-        r,
-        c,
-        r_max,
-        c_max,
-    )
+    neighbors_arbitray(&NEIGHBORS_AND_SELF, r, c, r_max, c_max)
 }
 
 pub fn neighbors_diag(
@@ -172,22 +335,7 @@ pub fn neighbors_diag(
     r_max: usize,
     c_max: usize,
 ) -> impl Iterator<Item = (usize, usize)> {
-    neighbors_arbitray(
-        &[
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ],
-        r,
-        c,
-        r_max,
-        c_max,
-    )
+    neighbors_arbitray(&NEIGHBORS_DIAG, r, c, r_max, c_max)
 }
 
 pub fn neighbors_diag_and_self(
@@ -196,23 +344,173 @@ pub fn neighbors_diag_and_self(
     r_max: usize,
     c_max: usize,
 ) -> impl Iterator<Item = (usize, usize)> {
-    neighbors_arbitray(
-        &[
-            (0, 0),
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ],
-        r,
-        c,
-        r_max,
-        c_max,
-    )
+    neighbors_arbitray(&NEIGHBORS_DIAG_AND_SELF, r, c, r_max, c_max)
+}
+
+/// Wrap-around version of [`neighbors_arbitray`], neighbors past an edge come back in on the
+/// opposite side as if the grid was a torus.
+pub fn neighbors_wrapping_arbitrary(
+    diffs: &[(isize, isize)],
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    neighbors_tiled_arbitrary(diffs, r, c, r_max, c_max).map(|(_, p)| p)
+}
+
+pub fn neighbors_wrapping(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighbors_wrapping_arbitrary(&NEIGHBORS, r, c, r_max, c_max)
+}
+
+pub fn neighbors_wrapping_and_self(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighbors_wrapping_arbitrary(&NEIGHBORS_AND_SELF, r, c, r_max, c_max)
+}
+
+pub fn neighbors_diag_wrapping(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighbors_wrapping_arbitrary(&NEIGHBORS_DIAG, r, c, r_max, c_max)
+}
+
+pub fn neighbors_diag_wrapping_and_self(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighbors_wrapping_arbitrary(&NEIGHBORS_DIAG_AND_SELF, r, c, r_max, c_max)
+}
+
+/// Like [`neighbors_wrapping_arbitrary`], but also yields which copy of the grid the neighbor is
+/// in, relative to the current one. For a grid repeating infinitely in every direction, stepping
+/// up from row 0 gives tile offset `(-1, 0)` and row `r_max - 1`.
+pub fn neighbors_tiled_arbitrary(
+    diffs: &[(isize, isize)],
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = ((isize, isize), (usize, usize))> + '_ {
+    let (r_max, c_max) = (r_max as isize, c_max as isize);
+    diffs.iter().map(move |&(y, x)| {
+        let r_new = r as isize + y;
+        let c_new = c as isize + x;
+
+        (
+            (r_new.div_euclid(r_max), c_new.div_euclid(c_max)),
+            (
+                r_new.rem_euclid(r_max) as usize,
+                c_new.rem_euclid(c_max) as usize,
+            ),
+        )
+    })
+}
+
+pub fn neighbors_tiled(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = ((isize, isize), (usize, usize))> {
+    neighbors_tiled_arbitrary(&NEIGHBORS, r, c, r_max, c_max)
+}
+
+/// Unbounded version of [`neighbors_arbitray`] for signed coordinates, e.g. on an infinite plane.
+///
+/// * `bounds`, optional half-open box `((r_min, c_min), (r_max, c_max))`. Neighbors outside of it
+///   are skipped.
+pub fn neighbors_signed_arbitrary<'a, T>(
+    diffs: &'a [(isize, isize)],
+    r: T,
+    c: T,
+    bounds: Option<((T, T), (T, T))>,
+) -> impl Iterator<Item = (T, T)> + 'a
+where
+    T: num::PrimInt + num::Signed + 'a,
+{
+    diffs
+        .iter()
+        .map(move |&(y, x)| (r + T::from(y).unwrap(), c + T::from(x).unwrap()))
+        .filter(move |&(r, c)| {
+            bounds.is_none_or(|((r_min, c_min), (r_max, c_max))| {
+                (r_min..r_max).contains(&r) && (c_min..c_max).contains(&c)
+            })
+        })
+}
+
+pub fn neighbors_signed<T>(r: T, c: T) -> impl Iterator<Item = (T, T)>
+where
+    T: num::PrimInt + num::Signed + 'static,
+{
+    neighbors_signed_arbitrary(&NEIGHBORS, r, c, None)
+}
+
+pub fn neighbors_signed_and_self<T>(r: T, c: T) -> impl Iterator<Item = (T, T)>
+where
+    T: num::PrimInt + num::Signed + 'static,
+{
+    neighbors_signed_arbitrary(&NEIGHBORS_AND_SELF, r, c, None)
+}
+
+pub fn neighbors_diag_signed<T>(r: T, c: T) -> impl Iterator<Item = (T, T)>
+where
+    T: num::PrimInt + num::Signed + 'static,
+{
+    neighbors_signed_arbitrary(&NEIGHBORS_DIAG, r, c, None)
+}
+
+pub fn neighbors_diag_signed_and_self<T>(r: T, c: T) -> impl Iterator<Item = (T, T)>
+where
+    T: num::PrimInt + num::Signed + 'static,
+{
+    neighbors_signed_arbitrary(&NEIGHBORS_DIAG_AND_SELF, r, c, None)
+}
+
+/// Like [`neighbors_arbitray`], but yields the direction taken along with each neighbor.
+pub fn neighbors_arbitray_dir<D>(
+    dirs: &[D],
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (D, (usize, usize))> + '_
+where
+    D: Direction,
+{
+    dirs.iter()
+        .filter_map(move |&d| Some((d, d.step(r, c, r_max, c_max)?)))
+}
+
+pub fn neighbors_dir(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (Dir4, (usize, usize))> {
+    neighbors_arbitray_dir(Dir4::ALL, r, c, r_max, c_max)
+}
+
+pub fn neighbors_diag_dir(
+    r: usize,
+    c: usize,
+    r_max: usize,
+    c_max: usize,
+) -> impl Iterator<Item = (Dir8, (usize, usize))> {
+    neighbors_arbitray_dir(Dir8::ALL, r, c, r_max, c_max)
 }
 
 pub fn slice_get_mut_twice<T>(slice: &mut [T], index0: usize, index1: usize) -> (&mut T, &mut T) {
@@ -268,6 +566,63 @@ mod tests {
         assert_eq!(mod_pow(5, 3, 7), 6);
     }
 
+    #[test]
+    fn test_extended_gcd() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(-240, 46), (2, 9, 47));
+        assert_eq!(extended_gcd(0, -5), (5, 0, -1));
+        assert_eq!(extended_gcd(0, 0), (0, 1, 0));
+
+        for (a, b) in [
+            (35i64, 15),
+            (17, 5),
+            (-12, 18),
+            (1_000_000_007, 998_244_353),
+        ] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, num::integer::gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_checked_mod_inv() {
+        assert_eq!(checked_mod_inv(3, 11), Some(4));
+        assert_eq!(checked_mod_inv(3u32, 11), Some(4));
+        assert_eq!(checked_mod_inv(14, 11), Some(4));
+        // -3 = 8 mod 11, and 8 * 7 = 56 = 1 mod 11
+        assert_eq!(checked_mod_inv(-3, 11), Some(7));
+        assert_eq!(checked_mod_inv(4, 12), None);
+        assert_eq!(checked_mod_inv(0, 7), None);
+        assert_eq!(checked_mod_inv(3, 0), None);
+
+        assert_eq!(checked_mod_inv_signed(3, 11), Some(4));
+        assert_eq!(checked_mod_inv_signed(-3, 11), Some(7));
+        assert_eq!(checked_mod_inv_signed(3, -11), Some(4));
+        assert_eq!(checked_mod_inv_signed(6, 9), None);
+        assert_eq!(checked_mod_inv_signed(5, 1), Some(0));
+    }
+
+    #[test]
+    fn test_modular_arithmetic_large_modulus() {
+        // (m - 1)^2 = 1 mod m, which overflows u64 if multiplied directly
+        let m = (1u64 << 63) - 25;
+        assert_eq!(mod_pow(m - 1, 2, m), 1);
+        // Fermat's little theorem, m is prime
+        assert_eq!(mod_pow(3, m - 1, m), 1);
+        assert_eq!(mod_pow(3u128, (m - 1) as u128, m as u128), 1);
+        assert_eq!(mod_pow(3i64, m as i64 - 1, m as i64), 1);
+
+        let inv = mod_inv(3, m);
+        assert_eq!(inv.mul_mod(&3, &m), 1);
+
+        // 2^31 - 1 is prime, and the products overflow i32
+        let m = i32::MAX;
+        let target = mod_pow(7, 1_234_567, m);
+        let x = baby_step_giant_step(m, 7, target).unwrap();
+        assert_eq!(mod_pow(7, x, m), target);
+    }
+
     #[test]
     fn test_discrete_log_and_crt() {
         // baby_step_giant_step
@@ -278,6 +633,12 @@ mod tests {
         assert_eq!(baby_step_giant_step(11, 2, 5), Some(4));
         // Unsolvable
         assert_eq!(baby_step_giant_step(11, 2, 0), None);
+        // 4 has order 5 modulo 11, so 4^x = 5 has solutions 2, 7, ... and the smallest is returned
+        assert_eq!(baby_step_giant_step(11, 4, 5), Some(2));
+        assert_eq!(
+            baby_step_giant_step_bounded::<_, RandomState>(11, 4, 5, 2),
+            None
+        );
 
         // chinese_remainder_theorem
         // x = 2 mod 3
@@ -288,6 +649,50 @@ mod tests {
         assert_eq!(chinese_remainder_theorem(inputs.into_iter()), 23);
     }
 
+    #[test]
+    fn test_chinese_remainder_theorem_general() {
+        // Coprime moduli agree with the regular version
+        let inputs = [(2, 3), (3, 5), (2, 7)];
+        assert_eq!(chinese_remainder_theorem_general(inputs), Some((23, 105)));
+
+        // x = 3 mod 4, x = 5 mod 6 => x = 11 mod 12
+        assert_eq!(
+            chinese_remainder_theorem_general([(3u64, 4), (5, 6)]),
+            Some((11, 12))
+        );
+
+        // x = 1 mod 4, x = 2 mod 6 has no solution (parity mismatch)
+        assert_eq!(chinese_remainder_theorem_general([(1, 4), (2, 6)]), None);
+
+        // Negative residues are normalized
+        assert_eq!(
+            chinese_remainder_theorem_general([(-1i64, 4), (-1, 6)]),
+            Some((11, 12))
+        );
+
+        assert_eq!(
+            chinese_remainder_theorem_general(std::iter::empty::<(i32, i32)>()),
+            Some((0, 1))
+        );
+
+        // Moduli above half the range of the type
+        let m = u64::MAX - 58;
+        assert_eq!(
+            chinese_remainder_theorem_general([(m - 1, m)]),
+            Some((m - 1, m))
+        );
+        assert_eq!(
+            chinese_remainder_theorem_general([(-1, i64::MAX)]),
+            Some((i64::MAX - 1, i64::MAX))
+        );
+
+        // the combined modulus is above half the range as well
+        let m = (1u64 << 62) + 1;
+        let (x, lcm) = chinese_remainder_theorem_general([(2, 3), (m - 1, m)]).unwrap();
+        assert_eq!(lcm, 3 * m);
+        assert_eq!((x % 3, x % m), (2, m - 1));
+    }
+
     #[test]
     fn test_build_array() {
         let iter = vec![1, 2, 3].into_iter();
@@ -325,6 +730,90 @@ mod tests {
         assert_eq!(diag, expected_diag);
     }
 
+    #[test]
+    fn test_neighbors_wrapping() {
+        let mut corner: Vec<(usize, usize)> = neighbors_wrapping(0, 0, 3, 4).collect();
+        corner.sort();
+        assert_eq!(corner, vec![(0, 1), (0, 3), (1, 0), (2, 0)]);
+
+        assert_eq!(neighbors_wrapping_and_self(2, 3, 3, 4).count(), 5);
+        let mut diag: Vec<(usize, usize)> = neighbors_diag_wrapping(2, 3, 3, 4).collect();
+        diag.sort();
+        #[rustfmt::skip]
+        let expected = vec![
+            (0, 0), (0, 2), (0, 3),
+            (1, 0), (1, 2), (1, 3),
+            (2, 0), (2, 2),
+        ];
+        assert_eq!(diag, expected);
+        assert!(neighbors_diag_wrapping_and_self(1, 1, 3, 3).any(|p| p == (1, 1)));
+
+        // robots moving several cells per second
+        let robot: Vec<(usize, usize)> =
+            neighbors_wrapping_arbitrary(&[(-3, 7)], 1, 2, 7, 11).collect();
+        assert_eq!(robot, vec![(5, 9)]);
+    }
+
+    #[test]
+    fn test_neighbors_tiled() {
+        let mut tiles: Vec<_> = neighbors_tiled(0, 4, 5, 5).collect();
+        tiles.sort();
+        assert_eq!(
+            tiles,
+            vec![
+                ((-1, 0), (4, 4)),
+                ((0, 0), (0, 3)),
+                ((0, 0), (1, 4)),
+                ((0, 1), (0, 0)),
+            ]
+        );
+
+        let far: Vec<_> = neighbors_tiled_arbitrary(&[(-11, 12)], 0, 0, 5, 5).collect();
+        assert_eq!(far, vec![((-3, 2), (4, 2))]);
+    }
+
+    #[test]
+    fn test_neighbors_signed() {
+        let mut normal: Vec<(i64, i64)> = neighbors_signed(0, 0).collect();
+        normal.sort();
+        assert_eq!(normal, vec![(-1, 0), (0, -1), (0, 1), (1, 0)]);
+
+        assert_eq!(neighbors_signed_and_self(5isize, -5).count(), 5);
+        assert_eq!(neighbors_diag_signed(i64::MIN + 1, 0).count(), 8);
+        assert!(neighbors_diag_signed_and_self(3i32, 4).any(|p| p == (3, 4)));
+
+        // the unsigned helpers are the signed ones clamped to [0, max)
+        let bounded: Vec<(isize, isize)> =
+            neighbors_signed_arbitrary(&NEIGHBORS_DIAG, 0, 2, Some(((0, 0), (3, 3)))).collect();
+        let unsigned: Vec<(isize, isize)> = neighbors_diag(0, 2, 3, 3)
+            .map(|(r, c)| (r as isize, c as isize))
+            .collect();
+        assert_eq!(bounded, unsigned);
+        assert_eq!(bounded, vec![(0, 1), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_neighbors_dir() {
+        let corner: Vec<_> = neighbors_dir(0, 0, 3, 3).collect();
+        assert_eq!(corner, vec![(Dir4::East, (0, 1)), (Dir4::South, (1, 0))]);
+
+        let diag: Vec<_> = neighbors_diag_dir(2, 0, 3, 3).collect();
+        assert_eq!(
+            diag,
+            vec![
+                (Dir8::North, (1, 0)),
+                (Dir8::NorthEast, (1, 1)),
+                (Dir8::East, (2, 1))
+            ]
+        );
+
+        let mut plain: Vec<_> = neighbors_diag_dir(1, 1, 3, 3).map(|(_, p)| p).collect();
+        let mut expected: Vec<_> = neighbors_diag(1, 1, 3, 3).collect();
+        plain.sort();
+        expected.sort();
+        assert_eq!(plain, expected);
+    }
+
     #[test]
     fn test_slice_get_mut_twice() {
         let mut data = vec![100, 200, 300];
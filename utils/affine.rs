@@ -0,0 +1,151 @@
+use super::{MyInteger, add_mod, checked_mod_inv};
+
+/// The affine map `x -> a * x + b (mod m)`.
+///
+/// Linear maps modulo `m` are closed under composition, so a long sequence of them (such as
+/// shuffling a deck of cards) collapses into a single `Affine`, which can then be repeated an
+/// astronomical number of times with [`Affine::pow`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Affine<T> {
+    pub a: T,
+    pub b: T,
+    pub m: T,
+}
+
+impl<T> Affine<T>
+where
+    T: MyInteger,
+{
+    /// Creates `x -> a * x + b (mod m)`, normalizing negative coefficients into `[0, m)`.
+    pub fn new(a: T, b: T, m: T) -> Self {
+        Self {
+            a: a.mod_floor(&m),
+            b: b.mod_floor(&m),
+            m,
+        }
+    }
+
+    /// The map `x -> x (mod m)`.
+    pub fn identity(m: T) -> Self {
+        Self::new(T::one(), T::zero(), m)
+    }
+
+    pub fn apply(&self, x: T) -> T {
+        let ax = self.a.mul_mod(&x.mod_floor(&self.m), &self.m);
+        add_mod(ax, self.b.clone(), &self.m)
+    }
+
+    /// Returns `self ∘ other`, the map that applies `other` first and then `self`.
+    pub fn compose(&self, other: &Self) -> Self {
+        debug_assert!(self.m == other.m, "moduli differ");
+        Self {
+            a: self.a.mul_mod(&other.a, &self.m),
+            b: self.apply(other.b.clone()),
+            m: self.m.clone(),
+        }
+    }
+
+    /// Returns the map that applies `self` first and then `other`.
+    pub fn then(&self, other: &Self) -> Self {
+        other.compose(self)
+    }
+
+    /// Returns `self` applied `n` times, using exponentiation by squaring.
+    pub fn pow(&self, mut n: T) -> Self {
+        let two = T::one() + T::one();
+        let mut result = Self::identity(self.m.clone());
+        let mut base = self.clone();
+
+        while n > T::zero() {
+            if n.is_odd() {
+                result = result.compose(&base);
+            }
+            n = n / two.clone();
+            base = base.compose(&base);
+        }
+
+        result
+    }
+
+    /// Returns the map that undoes `self`, or `None` if `a` isn't invertible modulo `m`.
+    pub fn inverse(&self) -> Option<Self> {
+        // y = a * x + b  =>  x = a^-1 * y - a^-1 * b
+        let a_inv = checked_mod_inv(self.a.clone(), self.m.clone())?;
+        let b = a_inv.mul_mod(&self.b, &self.m);
+
+        Some(Self {
+            a: a_inv,
+            b: (self.m.clone() - b) % self.m.clone(),
+            m: self.m.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affine_shuffle() {
+        // Positions of cards in a deck of 10 after each technique
+        let new_stack = Affine::new(-1i32, -1, 10);
+        let cut = |n: i32| Affine::new(1, -n, 10);
+        let increment = |n: i32| Affine::new(n, 0, 10);
+
+        let shuffle = increment(7).then(&new_stack).then(&new_stack);
+        let mut deck = [0; 10];
+        for card in 0..10 {
+            deck[shuffle.apply(card) as usize] = card;
+        }
+        assert_eq!(deck, [0, 3, 6, 9, 2, 5, 8, 1, 4, 7]);
+
+        let shuffle = new_stack
+            .then(&cut(-2))
+            .then(&increment(7))
+            .then(&cut(8))
+            .then(&cut(-4))
+            .then(&increment(7))
+            .then(&cut(3))
+            .then(&increment(9))
+            .then(&increment(3))
+            .then(&cut(-1));
+        let mut deck = [0; 10];
+        for card in 0..10 {
+            deck[shuffle.apply(card) as usize] = card;
+        }
+        assert_eq!(deck, [9, 2, 5, 8, 1, 4, 7, 0, 3, 6]);
+    }
+
+    #[test]
+    fn test_affine_pow_and_inverse() {
+        let f = Affine::new(7i64, 3, 101);
+        let mut x = 5;
+        for n in 0..20 {
+            assert_eq!(f.pow(n).apply(5), x);
+            x = f.apply(x);
+        }
+
+        let inv = f.inverse().unwrap();
+        assert_eq!(inv.compose(&f), Affine::identity(101));
+        assert_eq!(f.compose(&inv), Affine::identity(101));
+        assert_eq!(Affine::new(4, 1, 10).inverse(), None);
+    }
+
+    #[test]
+    fn test_affine_large() {
+        let m: i128 = 119_315_717_514_047;
+        let f = Affine::new(-7_919, 12_345, m);
+        let n: i128 = 101_741_582_076_661;
+
+        let fx = f.pow(n);
+        assert_eq!(fx.compose(&f.pow(m - 1 - n)), f.pow(m - 1));
+        assert_eq!(fx.inverse().unwrap().apply(fx.apply(2020)), 2020);
+
+        // near the limit of u64
+        let m = u64::MAX - 58; // largest u64 prime
+        let f = Affine::new(m - 1, m - 1, m);
+        assert_eq!(f.apply(m - 1), 0);
+        assert_eq!(f.pow(2), Affine::identity(m));
+    }
+}
@@ -0,0 +1,242 @@
+//! Discrete logarithms and multiplicative orders on `u64`, built on
+//! [`baby_step_giant_step_bounded`] and the `u64` factorization in
+//! [`number_theory`](super::number_theory).
+
+use super::{
+    MulMod, baby_step_giant_step_bounded, checked_mod_inv, chinese_remainder_theorem_general,
+    mod_pow,
+    number_theory::{factorize, totient},
+};
+use num::integer::gcd;
+use std::hash::{BuildHasher, RandomState};
+
+/// Returns the multiplicative order of `base` modulo `modulus`, the smallest `k > 0` such that
+/// `base^k = 1`, or `None` if `base` isn't coprime to `modulus`.
+pub fn multiplicative_order(base: u64, modulus: u64) -> Option<u64> {
+    if modulus == 1 {
+        return Some(1);
+    }
+    if gcd(base % modulus, modulus) != 1 {
+        return None;
+    }
+
+    let phi = totient(modulus);
+    let mut order = phi;
+    for (p, _) in factorize(phi) {
+        while order.is_multiple_of(p) && mod_pow(base, order / p, modulus) == 1 {
+            order /= p;
+        }
+    }
+
+    Some(order)
+}
+
+/// Solves `base^x = target (mod modulus)` and returns the smallest non-negative `x`.
+/// See [`discrete_log_with_period`].
+pub fn discrete_log(modulus: u64, base: u64, target: u64) -> Option<u64> {
+    discrete_log_with_period::<RandomState>(modulus, base, target).map(|(x, _)| x)
+}
+
+/// Discrete logarithm for any modulus, using Pohlig–Hellman to split the problem into one
+/// baby-step giant-step per prime factor of the order of `base`. `S` is the hasher used for the
+/// baby-step tables.
+///
+/// Returns the smallest non-negative `x` such that `base^x = target (mod modulus)` along with the
+/// period of the solutions: every `x + k * period` is also a solution. When `base` shares a factor
+/// with `modulus` the powers of `base` aren't purely periodic, so a solution found before the
+/// sequence starts repeating has no period.
+pub fn discrete_log_with_period<S>(
+    modulus: u64,
+    base: u64,
+    target: u64,
+) -> Option<(u64, Option<u64>)>
+where
+    S: BuildHasher + Default,
+{
+    if modulus == 1 {
+        return Some((0, Some(1)));
+    }
+
+    let base = base % modulus;
+    let target = target % modulus;
+
+    // 0^0 = 1 and every later power is 0
+    if base == 0 {
+        return match target {
+            1 => Some((0, None)),
+            0 => Some((1, Some(1))),
+            _ => None,
+        };
+    }
+
+    // Split the modulus into the part sharing primes with the base, and the part coprime to it.
+    // base^x is 0 modulo the shared part once x >= preperiod.
+    let mut shared = 1;
+    let mut preperiod = 0;
+    for (p, e) in factorize(modulus) {
+        if base.is_multiple_of(p) {
+            let mut v = 0;
+            let mut rest = base;
+            while rest.is_multiple_of(p) {
+                rest /= p;
+                v += 1;
+            }
+            shared *= p.pow(e);
+            preperiod = preperiod.max(e.div_ceil(v) as u64);
+        }
+    }
+
+    let mut power = 1 % modulus;
+    for x in 0..preperiod {
+        if power == target {
+            return Some((x, None));
+        }
+        power = power.mul_mod(&base, &modulus);
+    }
+
+    if !target.is_multiple_of(shared) {
+        return None;
+    }
+
+    let coprime = modulus / shared;
+    let order = multiplicative_order(base, coprime)?;
+    let x = pohlig_hellman::<S>(coprime, base % coprime, target % coprime, order)?;
+
+    // smallest solution that is past the preperiod
+    let x = if x < preperiod {
+        x + (preperiod - x).div_ceil(order) * order
+    } else {
+        x
+    };
+
+    Some((x, Some(order)))
+}
+
+/// Solves `base^x = target` where `base` is a unit of order `order`. Returns `x` in `0..order`.
+fn pohlig_hellman<S>(modulus: u64, base: u64, target: u64, order: u64) -> Option<u64>
+where
+    S: BuildHasher + Default,
+{
+    let mut congruences = Vec::new();
+
+    for (p, e) in factorize(order) {
+        let pe = p.pow(e);
+        let g = mod_pow(base, order / pe, modulus);
+        let h = mod_pow(target, order / pe, modulus);
+        let g_inv = checked_mod_inv(g, modulus)?;
+
+        // gamma has order p, so each base p digit of x is a discrete log in a group of size p
+        let gamma = mod_pow(g, pe / p, modulus);
+        let mut x = 0;
+        let mut pk = 1;
+        for _ in 0..e {
+            let h_k = mod_pow(
+                mod_pow(g_inv, x, modulus).mul_mod(&h, &modulus),
+                pe / pk / p,
+                modulus,
+            );
+            let d = baby_step_giant_step_bounded::<_, S>(modulus, gamma, h_k, p)?;
+            x += d * pk;
+            pk *= p;
+        }
+
+        congruences.push((x, pe));
+    }
+
+    let (x, _) = chinese_remainder_theorem_general(congruences)?;
+    (mod_pow(base, x, modulus) == target % modulus).then_some(x)
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiplicative_order() {
+        assert_eq!(multiplicative_order(2, 7), Some(3));
+        assert_eq!(multiplicative_order(3, 7), Some(6));
+        assert_eq!(multiplicative_order(2, 9), Some(6));
+        assert_eq!(multiplicative_order(6, 9), None);
+        assert_eq!(multiplicative_order(5, 1), Some(1));
+    }
+
+    #[test]
+    fn test_discrete_log_prime() {
+        assert_eq!(discrete_log(11, 2, 8), Some(3));
+        assert_eq!(discrete_log(11, 2, 5), Some(4));
+        assert_eq!(discrete_log(11, 2, 0), None);
+        assert_eq!(discrete_log(11, 2, 1), Some(0));
+
+        // 2^x = 8 mod 7, 2 has order 3 so the solutions are 0, 3, 6, ...
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(7, 2, 8),
+            Some((0, Some(3)))
+        );
+        // 3 isn't a power of 2 modulo 7
+        assert_eq!(discrete_log(7, 2, 3), None);
+
+        // AoC 2020 day 25 example
+        assert_eq!(discrete_log(20_201_227, 7, 5_764_801), Some(8));
+        assert_eq!(discrete_log(20_201_227, 7, 17_807_724), Some(11));
+    }
+
+    #[test]
+    fn test_discrete_log_composite() {
+        let modulus = 1_000_000_007 * 998_244_353;
+        let target = mod_pow(3, 123_456_789_012, modulus);
+        let (x, period) = discrete_log_with_period::<RandomState>(modulus, 3, target).unwrap();
+        assert_eq!(mod_pow(3, x, modulus), target);
+        assert_eq!(period, multiplicative_order(3, modulus));
+        assert!(x < period.unwrap());
+
+        // 3 has order 4 modulo 10: 1, 3, 9, 7
+        assert_eq!(discrete_log(10, 3, 7), Some(3));
+        assert_eq!(discrete_log(10, 3, 5), None);
+    }
+
+    #[test]
+    fn test_discrete_log_non_unit() {
+        // 2^x mod 12: 1, 2, 4, 8, 4, 8, ...
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(12, 2, 1),
+            Some((0, None))
+        );
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(12, 2, 2),
+            Some((1, None))
+        );
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(12, 2, 4),
+            Some((2, Some(2)))
+        );
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(12, 2, 8),
+            Some((3, Some(2)))
+        );
+        assert_eq!(discrete_log(12, 2, 6), None);
+
+        // 6^x mod 36 is 0 for x >= 2
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(36, 6, 0),
+            Some((2, Some(1)))
+        );
+
+        // a base of 0 only reaches 1 and 0
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(12, 0, 1),
+            Some((0, None))
+        );
+        assert_eq!(
+            discrete_log_with_period::<RandomState>(7, 14, 0),
+            Some((1, Some(1)))
+        );
+        assert_eq!(discrete_log(12, 0, 5), None);
+
+        // 3^41 doesn't fit in a u64
+        let base = 3u64.pow(40);
+        let modulus = base + 3;
+        assert_eq!(discrete_log(modulus, base, base), Some(1));
+        assert_eq!(discrete_log(modulus, base, 1), Some(0));
+    }
+}
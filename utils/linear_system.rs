@@ -0,0 +1,429 @@
+use super::MyInteger;
+use crate::common::GetMutTwice;
+use num::{Signed, Zero, rational::Ratio};
+
+/// Result of solving `A·x = b` exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution<T>
+where
+    T: Clone + num::Integer,
+{
+    /// The only solution.
+    Unique(Vec<Ratio<T>>),
+    /// A solution with every free variable set to zero, along with the indices of the free
+    /// variables. Any value can be assigned to the free variables, see [`Reduced::solve_with`].
+    Infinite {
+        particular: Vec<Ratio<T>>,
+        free: Vec<usize>,
+    },
+    /// The system is inconsistent.
+    None,
+}
+
+impl<T> Solution<T>
+where
+    T: Clone + num::Integer,
+{
+    /// Returns the solution if it is unique.
+    pub fn unique(self) -> Option<Vec<Ratio<T>>> {
+        match self {
+            Self::Unique(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// The augmented matrix `[A | b]` in reduced row echelon form.
+#[derive(Debug, Clone)]
+pub struct Reduced<T> {
+    /// The reduced augmented matrix. Row `i` has a leading one in column `pivots[i]`, and the rows
+    /// after `pivots.len()` are all zero in `A`.
+    pub matrix: Vec<Vec<Ratio<T>>>,
+    /// The pivot column of each non-zero row, in ascending order.
+    pub pivots: Vec<usize>,
+    /// The number of variables.
+    pub cols: usize,
+}
+
+impl<T> Reduced<T>
+where
+    T: MyInteger + Signed,
+{
+    /// Reduces `[a | b]` using Gauss-Jordan elimination over exact rationals.
+    pub fn new<A>(a: &[A], b: &[T]) -> Self
+    where
+        A: AsRef<[T]>,
+    {
+        assert_eq!(a.len(), b.len(), "mismatched number of equations");
+        let rows = a.len();
+        let cols = a.first().map_or(0, |row| row.as_ref().len());
+
+        let mut matrix: Vec<Vec<Ratio<T>>> = a
+            .iter()
+            .zip(b)
+            .map(|(row, rhs)| {
+                let row = row.as_ref();
+                assert_eq!(row.len(), cols, "not a rectangular matrix");
+                row.iter()
+                    .chain(std::iter::once(rhs))
+                    .map(|x| Ratio::from_integer(x.clone()))
+                    .collect()
+            })
+            .collect();
+
+        let mut pivots = Vec::new();
+        for c in 0..cols {
+            let r = pivots.len();
+            if r == rows {
+                break;
+            }
+            let Some(p) = (r..rows).find(|&p| !matrix[p][c].is_zero()) else {
+                continue;
+            };
+            matrix.swap(r, p);
+
+            let lead = matrix[r][c].clone();
+            for x in matrix[r].iter_mut() {
+                *x = x.clone() / lead.clone();
+            }
+
+            for i in 0..rows {
+                if i == r || matrix[i][c].is_zero() {
+                    continue;
+                }
+                let (pivot_row, row) = matrix.get_mut_twice(r, i);
+                let factor = row[c].clone();
+                for (x, p) in row[c..].iter_mut().zip(&pivot_row[c..]) {
+                    *x = x.clone() - factor.clone() * p.clone();
+                }
+            }
+
+            pivots.push(c);
+        }
+
+        Self {
+            matrix,
+            pivots,
+            cols,
+        }
+    }
+
+    /// Returns `false` if a row reduced to `0 = c` with `c != 0`.
+    pub fn is_consistent(&self) -> bool {
+        self.matrix[self.pivots.len()..]
+            .iter()
+            .all(|row| row[self.cols].is_zero())
+    }
+
+    /// Returns the indices of the variables without a pivot.
+    pub fn free_variables(&self) -> Vec<usize> {
+        let mut pivots = self.pivots.iter().peekable();
+        (0..self.cols)
+            .filter(|c| pivots.next_if_eq(&c).is_none())
+            .collect()
+    }
+
+    /// Completes a solution from values for the free variables, given in the same order as
+    /// [`Reduced::free_variables`]. Assumes the system is consistent.
+    pub fn solve_with(&self, free_values: &[Ratio<T>]) -> Vec<Ratio<T>> {
+        let free = self.free_variables();
+        assert_eq!(free.len(), free_values.len(), "wrong number of free values");
+
+        let mut x = vec![Ratio::zero(); self.cols];
+        for (&f, v) in free.iter().zip(free_values) {
+            x[f] = v.clone();
+        }
+
+        for (row, &p) in self.matrix.iter().zip(&self.pivots) {
+            x[p] = free
+                .iter()
+                .zip(free_values)
+                .fold(row[self.cols].clone(), |acc, (&f, v)| {
+                    acc - row[f].clone() * v.clone()
+                });
+        }
+
+        x
+    }
+
+    pub fn solution(&self) -> Solution<T> {
+        if !self.is_consistent() {
+            return Solution::None;
+        }
+
+        let free = self.free_variables();
+        let particular = self.solve_with(&vec![Ratio::zero(); free.len()]);
+        if free.is_empty() {
+            Solution::Unique(particular)
+        } else {
+            Solution::Infinite { particular, free }
+        }
+    }
+}
+
+/// Solves `A·x = b` exactly using Gaussian elimination over rationals. Use `i128` or `BigInt` if
+/// the intermediate fractions may grow large.
+pub fn solve_linear_system<T, A>(a: &[A], b: &[T]) -> Solution<T>
+where
+    T: MyInteger + Signed,
+    A: AsRef<[T]>,
+{
+    Reduced::new(a, b).solution()
+}
+
+/// Minimizes `sum(x)` over non-negative integer `x` subject to `A·x = b`, where every entry of `A`
+/// and `b` is non-negative. Returns the optimal assignment, or `None` if there isn't one.
+///
+/// The system is reduced with Gaussian elimination, then every assignment of the free variables is
+/// enumerated. Since `A` is non-negative, each variable is bounded by `b[i] / A[i][j]` for every
+/// equation `i` it appears in, which keeps the search finite. This is fast when there are only a
+/// handful of free variables.
+pub fn min_nonneg_integer_solution<T, A>(a: &[A], b: &[T]) -> Option<Vec<T>>
+where
+    T: MyInteger + Signed,
+    A: AsRef<[T]>,
+{
+    assert!(
+        a.iter()
+            .flat_map(|row| row.as_ref())
+            .all(|x| !x.is_negative()),
+        "coefficients must be non-negative"
+    );
+    if b.iter().any(|x| x.is_negative()) {
+        return None;
+    }
+
+    let reduced = Reduced::new(a, b);
+    if !reduced.is_consistent() {
+        return None;
+    }
+
+    let free = reduced.free_variables();
+    let bounds: Vec<T> = free
+        .iter()
+        .map(|&j| {
+            a.iter()
+                .map(|row| &row.as_ref()[j])
+                .zip(b)
+                .filter(|(coef, _)| !coef.is_zero())
+                .map(|(coef, rhs)| rhs.clone() / coef.clone())
+                .min()
+                // a variable that isn't in any equation should just be 0
+                .unwrap_or_else(T::zero)
+        })
+        .collect();
+
+    // Scale each pivot row to integers: den * x[pivot] = rhs - sum(coefs[k] * x[free[k]])
+    let rows: Vec<(T, T, Vec<T>)> = reduced
+        .matrix
+        .iter()
+        .take(reduced.pivots.len())
+        .map(|row| {
+            let den = free
+                .iter()
+                .map(|&f| &row[f])
+                .chain(std::iter::once(&row[reduced.cols]))
+                .fold(T::one(), |acc, x| acc.lcm(x.denom()));
+            let scale = |x: &Ratio<T>| (x.clone() * den.clone()).to_integer();
+            let coefs = free.iter().map(|&f| scale(&row[f])).collect();
+
+            (den.clone(), scale(&row[reduced.cols]), coefs)
+        })
+        .collect();
+
+    let mut search = MinSearch {
+        bounds: &bounds,
+        rows: &rows,
+        values: Vec::with_capacity(free.len()),
+        pivot_values: Vec::with_capacity(rows.len()),
+        best: None,
+    };
+    search.search(T::zero());
+
+    let (_, free_values, pivot_values) = search.best?;
+    let mut x = vec![T::zero(); reduced.cols];
+    for (&f, v) in free.iter().zip(free_values) {
+        x[f] = v;
+    }
+    for (&p, v) in reduced.pivots.iter().zip(pivot_values) {
+        x[p] = v;
+    }
+
+    Some(x)
+}
+
+struct MinSearch<'a, T> {
+    bounds: &'a [T],
+    rows: &'a [(T, T, Vec<T>)],
+    values: Vec<T>,
+    pivot_values: Vec<T>,
+    best: Option<(T, Vec<T>, Vec<T>)>,
+}
+
+impl<T> MinSearch<'_, T>
+where
+    T: MyInteger + Signed,
+{
+    fn search(&mut self, sum: T) {
+        if self.best.as_ref().is_some_and(|(best, _, _)| sum >= *best) {
+            return;
+        }
+
+        let k = self.values.len();
+        if k < self.bounds.len() {
+            let mut v = T::zero();
+            while v <= self.bounds[k] {
+                self.values.push(v.clone());
+                self.search(sum.clone() + v.clone());
+                self.values.pop();
+                v = v + T::one();
+            }
+            return;
+        }
+
+        self.pivot_values.clear();
+        let mut total = sum;
+        for (den, rhs, coefs) in self.rows {
+            let scaled = coefs
+                .iter()
+                .zip(&self.values)
+                .fold(rhs.clone(), |acc, (c, v)| acc - c.clone() * v);
+            let (x, rem) = scaled.div_rem(den);
+            if !rem.is_zero() || x.is_negative() {
+                return;
+            }
+            total = total + x.clone();
+            self.pivot_values.push(x);
+        }
+
+        if self.best.as_ref().is_none_or(|(best, _, _)| total < *best) {
+            self.best = Some((total, self.values.clone(), self.pivot_values.clone()));
+        }
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratios(v: &[i64]) -> Vec<Ratio<i64>> {
+        v.iter().map(|&x| Ratio::from_integer(x)).collect()
+    }
+
+    #[test]
+    fn test_unique() {
+        // Claw machine: 94a + 22b = 8400, 34a + 67b = 5400
+        let a = [[94, 22], [34, 67]];
+        assert_eq!(
+            solve_linear_system(&a, &[8400, 5400]),
+            Solution::Unique(ratios(&[80, 40]))
+        );
+
+        // Non-integer solution: 2x = 1
+        assert_eq!(
+            solve_linear_system(&[[2]], &[1]),
+            Solution::Unique(vec![Ratio::new(1, 2)])
+        );
+
+        // Over-determined but consistent, requires a row swap
+        let a = [[0, 1, 1], [1, 0, 1], [1, 1, 0], [1, 1, 1]];
+        assert_eq!(
+            solve_linear_system(&a, &[5, 4, 3, 6]),
+            Solution::Unique(ratios(&[1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_none_and_infinite() {
+        // x + y = 1, x + y = 2
+        assert_eq!(
+            solve_linear_system(&[[1, 1], [1, 1]], &[1, 2]),
+            Solution::None
+        );
+
+        // x + y + z = 6, y - z = 1
+        let a = [vec![1, 1, 1], vec![0, 1, -1]];
+        let reduced = Reduced::new(&a, &[6, 1]);
+        assert_eq!(reduced.free_variables(), vec![2]);
+        assert_eq!(
+            reduced.solution(),
+            Solution::Infinite {
+                particular: ratios(&[5, 1, 0]),
+                free: vec![2],
+            }
+        );
+        assert_eq!(reduced.solve_with(&ratios(&[3])), ratios(&[-1, 4, 3]));
+    }
+
+    #[test]
+    fn test_min_nonneg_integer_solution() {
+        // Buttons increment the counters they list, find the fewest presses to reach the targets
+        fn presses(buttons: &[&[usize]], targets: &[i64]) -> Option<i64> {
+            let a: Vec<Vec<i64>> = (0..targets.len())
+                .map(|i| {
+                    buttons
+                        .iter()
+                        .map(|button| button.contains(&i) as i64)
+                        .collect()
+                })
+                .collect();
+
+            let x = min_nonneg_integer_solution(&a, targets)?;
+            for (row, target) in a.iter().zip(targets) {
+                assert_eq!(row.iter().zip(&x).map(|(a, x)| a * x).sum::<i64>(), *target);
+            }
+            Some(x.iter().sum())
+        }
+
+        assert_eq!(
+            presses(
+                &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]],
+                &[3, 5, 4, 7]
+            ),
+            Some(10)
+        );
+        assert_eq!(
+            presses(
+                &[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]],
+                &[7, 5, 12, 7, 2]
+            ),
+            Some(12)
+        );
+        assert_eq!(
+            presses(
+                &[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]],
+                &[10, 11, 11, 5, 10, 5]
+            ),
+            Some(11)
+        );
+
+        // A button that touches nothing is never pressed
+        assert_eq!(presses(&[&[0], &[]], &[4]), Some(4));
+        // 2x = 3 has no integer solution
+        assert_eq!(min_nonneg_integer_solution(&[[2]], &[3]), None);
+        // x + y = 1, x + y = 2
+        assert_eq!(
+            min_nonneg_integer_solution(&[[1, 1], [1, 1]], &[1, 2]),
+            None
+        );
+        // A negative target can't be reached
+        assert_eq!(min_nonneg_integer_solution(&[[1, 1]], &[-1]), None);
+    }
+
+    #[test]
+    fn test_large_values() {
+        // Coefficients in the range of the hailstone puzzles
+        let a: [[i128; 2]; 2] = [
+            [-123_456_789_012, 987_654_321],
+            [555_555_555, -44_444_444_444],
+        ];
+        let x = [31_415_926_535i128, -27_182_818_284];
+        let b = [
+            a[0][0] * x[0] + a[0][1] * x[1],
+            a[1][0] * x[0] + a[1][1] * x[1],
+        ];
+        let solution = solve_linear_system(&a, &b).unique().unwrap();
+        assert_eq!(solution, x.map(Ratio::from_integer));
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::{Add, Sub};
+
+/// Modular multiplication that doesn't overflow when the modulus is close to the limit of the type.
+///
+/// Primitives up to 64 bits widen to 128 bits. 128-bit integers multiply directly when the product
+/// fits, and fall back to multiplication by doubling otherwise. Big integers can't overflow, so they
+/// just multiply and reduce.
+///
+/// Like the `%` operator, the sign of the result follows the sign of `self * rhs`.
+pub trait MulMod: Sized {
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self;
+}
+
+macro_rules! widening {
+    ($($t:ty => $w:ty),+) => {
+        $(
+            impl MulMod for $t {
+                #[inline]
+                fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+                    (<$w>::from(*self) * <$w>::from(*rhs) % <$w>::from(*modulus)) as $t
+                }
+            }
+        )+
+    };
+}
+
+widening! { u8 => u128, u16 => u128, u32 => u128, u64 => u128, i8 => i128, i16 => i128, i32 => i128, i64 => i128 }
+
+impl MulMod for usize {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        (*self as u128 * *rhs as u128 % *modulus as u128) as usize
+    }
+}
+
+impl MulMod for isize {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        (*self as i128 * *rhs as i128 % *modulus as i128) as isize
+    }
+}
+
+/// Computes `(x + y) % m` for `x, y` in `0..m` without evaluating `x + y`, which could overflow
+/// when `m` is close to the limit of the type.
+#[inline]
+pub fn add_mod<T>(x: T, y: T, m: &T) -> T
+where
+    T: Clone + PartialOrd + Add<Output = T> + Sub<Output = T>,
+{
+    let gap = m.clone() - y.clone();
+    if x >= gap { x - gap } else { x + y }
+}
+
+/// Computes `a * b % m` for `a, b < m` using multiplication by doubling.
+fn mul_mod_u128(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(p) = a.checked_mul(b) {
+        return p % m;
+    }
+
+    let mut result = 0;
+    let mut a = a;
+    let mut b = b;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, &m);
+        }
+        a = add_mod(a, a, &m);
+        b >>= 1;
+    }
+
+    result
+}
+
+impl MulMod for u128 {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        mul_mod_u128(self % modulus, rhs % modulus, *modulus)
+    }
+}
+
+impl MulMod for i128 {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        let m = modulus.unsigned_abs();
+        let r = mul_mod_u128(self.unsigned_abs() % m, rhs.unsigned_abs() % m, m) as i128;
+
+        if self.is_negative() != rhs.is_negative() {
+            -r
+        } else {
+            r
+        }
+    }
+}
+
+impl MulMod for num::BigInt {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        self * rhs % modulus
+    }
+}
+
+impl MulMod for num::BigUint {
+    #[inline]
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        self * rhs % modulus
+    }
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_mod_widening() {
+        assert_eq!(7u8.mul_mod(&9, &10), 3);
+        assert_eq!(250u8.mul_mod(&250, &251), 1);
+        assert_eq!((-7i32).mul_mod(&9, &10), -3);
+
+        let m = (1u64 << 63) - 25;
+        assert_eq!((m - 1).mul_mod(&(m - 1), &m), 1);
+        assert_eq!((i64::MAX - 1).mul_mod(&(i64::MAX - 1), &i64::MAX), 1);
+    }
+
+    #[test]
+    fn test_mul_mod_u128() {
+        assert_eq!(12u128.mul_mod(&12, &100), 44);
+
+        let m = u128::MAX - 158; // largest prime below 2^128
+        assert_eq!((m - 1).mul_mod(&(m - 1), &m), 1);
+        assert_eq!((m - 1).mul_mod(&2, &m), m - 2);
+
+        let m = i128::MAX;
+        assert_eq!((m - 1).mul_mod(&(m - 1), &m), 1);
+        assert_eq!((1 - m).mul_mod(&(m - 1), &m), -1);
+    }
+
+    #[test]
+    fn test_add_mod() {
+        assert_eq!(add_mod(3, 4, &5), 2);
+        assert_eq!(add_mod(1u8, 2, &5), 3);
+
+        let m = u64::MAX - 58;
+        assert_eq!(add_mod(m - 1, m - 1, &m), m - 2);
+        assert_eq!(add_mod(m - 1, 1, &m), 0);
+    }
+
+    #[test]
+    fn test_mul_mod_big() {
+        let a = num::BigInt::from(u64::MAX);
+        let m = num::BigInt::from(1_000_000_007);
+        assert_eq!(a.mul_mod(&a, &m), num::BigInt::from(114_944_269));
+    }
+}
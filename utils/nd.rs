@@ -0,0 +1,213 @@
+use crate::common::AddIsize;
+
+/// The `2 * D` face-adjacent offsets in `D` dimensions, ordered `-1, +1` per axis. Evaluate it in
+/// a `const` to get a table for [`neighbors_nd_arbitrary`], e.g.
+/// `const FACES: [[isize; 3]; 6] = face_offsets();`.
+pub const fn face_offsets<const D: usize, const N: usize>() -> [[isize; D]; N] {
+    assert!(N == 2 * D, "N must be 2 * D");
+
+    let mut offsets = [[0; D]; N];
+    let mut axis = 0;
+    while axis < D {
+        offsets[2 * axis][axis] = -1;
+        offsets[2 * axis + 1][axis] = 1;
+        axis += 1;
+    }
+    offsets
+}
+
+/// The `3^D - 1` offsets of the Moore neighborhood in `D` dimensions, every combination of
+/// `-1, 0, 1` except all zeros.
+pub const fn moore_offsets<const D: usize, const N: usize>() -> [[isize; D]; N] {
+    assert!(N + 1 == 3usize.pow(D as u32), "N must be 3^D - 1");
+
+    let mut offsets = [[0; D]; N];
+    let mut i = 0;
+    let mut k = 0;
+    while k <= N {
+        // k = N / 2 has every base 3 digit set to 1, which is the center
+        if k != N / 2 {
+            let mut rest = k;
+            let mut axis = 0;
+            while axis < D {
+                offsets[i][axis] = (rest % 3) as isize - 1;
+                rest /= 3;
+                axis += 1;
+            }
+            i += 1;
+        }
+        k += 1;
+    }
+    offsets
+}
+
+/// Marker for the dimension `D`, see [`NdOffsets`].
+pub struct Nd<const D: usize>;
+
+/// The offset tables of one dimension, evaluated at compile time by [`face_offsets`] and
+/// [`moore_offsets`]. Implemented for 1 to 6 dimensions, larger tables can be built the same way
+/// and passed to [`neighbors_nd_arbitrary`].
+pub trait NdOffsets<const D: usize> {
+    const FACES: &'static [[isize; D]];
+    const MOORE: &'static [[isize; D]];
+}
+
+macro_rules! nd_offsets {
+    ($($d:literal => $moore:literal),+) => {
+        $(
+            impl NdOffsets<$d> for Nd<$d> {
+                const FACES: &'static [[isize; $d]] = &face_offsets::<$d, { 2 * $d }>();
+                const MOORE: &'static [[isize; $d]] = &moore_offsets::<$d, $moore>();
+            }
+        )+
+    };
+}
+
+nd_offsets! { 1 => 2, 2 => 8, 3 => 26, 4 => 80, 5 => 242, 6 => 728 }
+
+fn add_signed<T, const D: usize>(p: [T; D], delta: [isize; D]) -> [T; D]
+where
+    T: num::PrimInt + num::Signed,
+{
+    std::array::from_fn(|i| p[i] + T::from(delta[i]).unwrap())
+}
+
+fn add_clamp<const D: usize>(
+    p: [usize; D],
+    delta: [isize; D],
+    max: [usize; D],
+) -> Option<[usize; D]> {
+    let mut out = p;
+    for ((x, d), m) in out.iter_mut().zip(delta).zip(max) {
+        *x = x.checked_add_isize_clamp(d, m)?;
+    }
+    Some(out)
+}
+
+/// Unbounded neighbors of a signed point in `D` dimensions using the offsets in `diffs`.
+pub fn neighbors_nd_arbitrary<'a, T, const D: usize>(
+    diffs: &'a [[isize; D]],
+    p: [T; D],
+) -> impl Iterator<Item = [T; D]> + 'a
+where
+    T: num::PrimInt + num::Signed + 'a,
+{
+    diffs.iter().map(move |&d| add_signed(p, d))
+}
+
+/// Neighbors of an unsigned point using the offsets in `diffs`, keeping every axis in `0..max`.
+pub fn neighbors_nd_arbitrary_clamp<const D: usize>(
+    diffs: &[[isize; D]],
+    p: [usize; D],
+    max: [usize; D],
+) -> impl Iterator<Item = [usize; D]> + '_ {
+    diffs.iter().filter_map(move |&d| add_clamp(p, d, max))
+}
+
+/// The `2 * D` face-adjacent neighbors of a signed point.
+pub fn neighbors_nd<T, const D: usize>(p: [T; D]) -> impl Iterator<Item = [T; D]>
+where
+    T: num::PrimInt + num::Signed + 'static,
+    Nd<D>: NdOffsets<D>,
+{
+    neighbors_nd_arbitrary(Nd::<D>::FACES, p)
+}
+
+/// The `3^D - 1` neighbors of a signed point, including diagonals.
+pub fn neighbors_nd_moore<T, const D: usize>(p: [T; D]) -> impl Iterator<Item = [T; D]>
+where
+    T: num::PrimInt + num::Signed + 'static,
+    Nd<D>: NdOffsets<D>,
+{
+    neighbors_nd_arbitrary(Nd::<D>::MOORE, p)
+}
+
+/// Face-adjacent neighbors of an unsigned point, keeping every axis in `0..max`.
+pub fn neighbors_nd_clamp<const D: usize>(
+    p: [usize; D],
+    max: [usize; D],
+) -> impl Iterator<Item = [usize; D]>
+where
+    Nd<D>: NdOffsets<D>,
+{
+    neighbors_nd_arbitrary_clamp(Nd::<D>::FACES, p, max)
+}
+
+/// Moore neighbors of an unsigned point, keeping every axis in `0..max`.
+pub fn neighbors_nd_moore_clamp<const D: usize>(
+    p: [usize; D],
+    max: [usize; D],
+) -> impl Iterator<Item = [usize; D]>
+where
+    Nd<D>: NdOffsets<D>,
+{
+    neighbors_nd_arbitrary_clamp(Nd::<D>::MOORE, p, max)
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const FACES_3D: [[isize; 3]; 6] = face_offsets();
+    const MOORE_4D: [[isize; 4]; 80] = moore_offsets();
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(FACES_3D.iter().collect::<HashSet<_>>().len(), 6);
+        assert_eq!(face_offsets::<2, 4>(), [[-1, 0], [1, 0], [0, -1], [0, 1]]);
+        assert!(!MOORE_4D.contains(&[0; 4]));
+        assert_eq!(MOORE_4D.iter().collect::<HashSet<_>>().len(), 80);
+
+        assert_eq!(Nd::<3>::FACES, FACES_3D);
+        assert_eq!(Nd::<4>::MOORE, MOORE_4D);
+        assert_eq!(Nd::<6>::MOORE.len(), 728);
+        assert_eq!(neighbors_nd_moore([0i64; 3]).count(), 26);
+        assert_eq!(
+            neighbors_nd_arbitrary(&MOORE_4D, [1i32, 2, 3, 4]).count(),
+            80
+        );
+    }
+
+    #[test]
+    fn test_surface_area() {
+        let cubes: HashSet<[i32; 3]> = [
+            [2, 2, 2],
+            [1, 2, 2],
+            [3, 2, 2],
+            [2, 1, 2],
+            [2, 3, 2],
+            [2, 2, 1],
+            [2, 2, 3],
+            [2, 2, 4],
+            [2, 2, 6],
+            [1, 2, 5],
+            [3, 2, 5],
+            [2, 1, 5],
+            [2, 3, 5],
+        ]
+        .into_iter()
+        .collect();
+
+        let area = cubes
+            .iter()
+            .flat_map(|&c| neighbors_nd(c))
+            .filter(|n| !cubes.contains(n))
+            .count();
+        assert_eq!(area, 64);
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(neighbors_nd_moore_clamp([0, 0, 0], [3, 3, 3]).count(), 7);
+        assert_eq!(neighbors_nd_moore_clamp([1, 1, 1], [3, 3, 3]).count(), 26);
+        let mut faces: Vec<_> = neighbors_nd_clamp([0, 2], [3, 3]).collect();
+        faces.sort();
+        assert_eq!(faces, vec![[0, 1], [1, 2]]);
+        assert_eq!(
+            neighbors_nd_arbitrary_clamp(&FACES_3D, [0, 0, 0], [1, 1, 2]).collect::<Vec<_>>(),
+            vec![[0, 0, 1]]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Number theory on 64-bit integers. Factorization and the multiplicative functions rely on
+//! Miller-Rabin and Pollard's rho for `u64`, so unlike the generic helpers in `utils` these aren't
+//! generic over `MyInteger`.
+
+use super::{MulMod, mod_pow};
+use num::integer::gcd;
+
+const SEGMENT_SIZE: usize = 1 << 16;
+
+/// Returns all primes `<= n` in ascending order. See [`primes_in_range`].
+pub fn sieve(n: usize) -> Vec<usize> {
+    primes_in_range(0, n as u64 + 1)
+        .into_iter()
+        .map(|p| p as usize)
+        .collect()
+}
+
+/// Segmented sieve of Eratosthenes returning the primes in `[lo, hi)` in ascending order.
+///
+/// Only the base primes up to `sqrt(hi)` and one segment are in memory at once, so this also works
+/// for windows far away from zero (e.g. `[10^12, 10^12 + 10^6)`).
+pub fn primes_in_range(lo: u64, hi: u64) -> Vec<u64> {
+    let lo = lo.max(2);
+    if lo >= hi {
+        return Vec::new();
+    }
+
+    let limit = (hi - 1).isqrt() as usize;
+    let mut composite = vec![false; limit + 1];
+    let mut base = Vec::new();
+    for i in 2..=limit {
+        if !composite[i] {
+            base.push(i as u64);
+            for j in (i * i..=limit).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+
+    let mut primes = Vec::new();
+    let mut segment = vec![true; SEGMENT_SIZE];
+    let mut start = lo;
+    while start < hi {
+        let end = hi.min(start + SEGMENT_SIZE as u64);
+        let segment = &mut segment[..(end - start) as usize];
+        segment.fill(true);
+
+        for &p in base.iter().take_while(|&&p| p * p < end) {
+            let first = (p * p).max(start.div_ceil(p) * p);
+            for m in (first..end).step_by(p as usize) {
+                segment[(m - start) as usize] = false;
+            }
+        }
+
+        primes.extend(
+            segment
+                .iter()
+                .enumerate()
+                .filter(|&(_, &is_prime)| is_prime)
+                .map(|(i, _)| start + i as u64),
+        );
+        start = end;
+    }
+
+    primes
+}
+
+/// Smallest prime factor table for `0..=n`, built with a linear sieve. Allows factorizing any
+/// number in the table in `O(log n)`.
+pub struct SmallestPrimeFactor {
+    spf: Vec<u32>,
+    primes: Vec<u32>,
+}
+
+impl SmallestPrimeFactor {
+    pub fn new(n: usize) -> Self {
+        assert!(n <= u32::MAX as usize, "table is too large");
+        let mut spf = vec![0; n + 1];
+        let mut primes = Vec::new();
+
+        for i in 2..=n {
+            if spf[i] == 0 {
+                spf[i] = i as u32;
+                primes.push(i as u32);
+            }
+
+            for &p in &primes {
+                let m = i * p as usize;
+                if p > spf[i] || m > n {
+                    break;
+                }
+                spf[m] = p;
+            }
+        }
+
+        Self { spf, primes }
+    }
+
+    /// Returns the smallest prime factor of `x`, or `0` for `0` and `1`.
+    pub fn get(&self, x: usize) -> usize {
+        self.spf[x] as usize
+    }
+
+    pub fn is_prime(&self, x: usize) -> bool {
+        x >= 2 && self.get(x) == x
+    }
+
+    /// Returns all primes in the table in ascending order.
+    pub fn primes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.primes.iter().map(|&p| p as usize)
+    }
+
+    /// Returns the prime factorization of `x` as `(prime, exponent)` pairs with ascending primes.
+    pub fn factorize(&self, mut x: usize) -> Vec<(usize, u32)> {
+        let mut factors: Vec<(usize, u32)> = Vec::new();
+
+        while x > 1 {
+            let p = self.get(x);
+            match factors.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => factors.push((p, 1)),
+            }
+            x /= p;
+        }
+
+        factors
+    }
+}
+
+/// Deterministic Miller–Rabin primality test for all `u64`.
+pub fn is_prime(n: u64) -> bool {
+    // These bases are sufficient for all n < 3.3 * 10^24
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for a in BASES {
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = x.mul_mod(&x, &n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/// Pollard's rho with Brent's cycle detection. Returns a non-trivial factor of the composite `n`.
+fn pollard_rho(n: u64) -> u64 {
+    const BATCH: u64 = 128;
+
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+
+    for c in 1.. {
+        let f = |x: u64| ((u128::from(x) * u128::from(x) + c) % u128::from(n)) as u64;
+        let (mut x, mut y, mut ys) = (0, 2, 2);
+        let (mut q, mut g, mut r) = (1, 1, 1);
+
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = q.mul_mod(&x.abs_diff(y), &n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+
+        if g == n {
+            // The batch overshot, so backtrack one step at a time
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+
+        if g != n {
+            return g;
+        }
+    }
+
+    unreachable!()
+}
+
+/// Returns the prime factorization of `n` as `(prime, exponent)` pairs with ascending primes,
+/// using trial division for small factors and Pollard's rho for the rest.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    fn split(n: u64, primes: &mut Vec<u64>) {
+        if n == 1 {
+            return;
+        }
+        if is_prime(n) {
+            primes.push(n);
+            return;
+        }
+
+        let d = pollard_rho(n);
+        split(d, primes);
+        split(n / d, primes);
+    }
+
+    let mut n = n;
+    let mut primes = Vec::new();
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while n > 0 && n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    if n > 0 {
+        split(n, &mut primes);
+    }
+    primes.sort_unstable();
+
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+
+    factors
+}
+
+/// Enumerates all divisors from a prime factorization. The output isn't sorted.
+pub fn divisors_from_factors(factors: &[(u64, u32)]) -> Vec<u64> {
+    let mut divisors = vec![1];
+
+    for &(p, e) in factors {
+        let len = divisors.len();
+        let mut pk = 1;
+        for _ in 0..e {
+            pk *= p;
+            for i in 0..len {
+                divisors.push(divisors[i] * pk);
+            }
+        }
+    }
+
+    divisors
+}
+
+/// Returns all divisors of `n` in ascending order.
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut divisors = divisors_from_factors(&factorize(n));
+    divisors.sort_unstable();
+    divisors
+}
+
+/// Returns σ(n), the sum of all divisors of `n`. Like [`divisors`], 0 has none.
+pub fn divisor_sum(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    factorize(n)
+        .into_iter()
+        .map(|(p, e)| (0..e).fold(1, |acc, _| acc * p + 1))
+        .product()
+}
+
+/// Returns σ(k) for every `k` in `0..=n`. Useful when the answer is the first `k` whose divisor sum
+/// exceeds some target.
+pub fn divisor_sum_sieve(n: usize) -> Vec<u64> {
+    let mut sums = vec![0; n + 1];
+
+    for d in 1..=n {
+        for m in (d..=n).step_by(d) {
+            sums[m] += d as u64;
+        }
+    }
+
+    sums
+}
+
+/// Returns Euler's totient φ(n), the count of `1..=n` coprime with `n`, or 0 for 0.
+pub fn totient(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    factorize(n)
+        .into_iter()
+        .fold(n, |acc, (p, _)| acc / p * (p - 1))
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sieve() {
+        assert_eq!(sieve(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(sieve(1), Vec::<usize>::new());
+        assert_eq!(sieve(2), vec![2]);
+        assert_eq!(sieve(1_000_000).len(), 78_498);
+
+        assert_eq!(
+            primes_in_range(1_000_000_000_000, 1_000_000_000_100),
+            vec![
+                1_000_000_000_039,
+                1_000_000_000_061,
+                1_000_000_000_063,
+                1_000_000_000_091
+            ]
+        );
+        assert_eq!(primes_in_range(20, 20), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_smallest_prime_factor() {
+        let spf = SmallestPrimeFactor::new(100);
+        assert_eq!(spf.get(91), 7);
+        assert_eq!(spf.get(97), 97);
+        assert!(spf.is_prime(97));
+        assert!(!spf.is_prime(1));
+        assert_eq!(spf.factorize(72), vec![(2, 3), (3, 2)]);
+        assert_eq!(spf.factorize(1), vec![]);
+        assert_eq!(spf.primes().collect::<Vec<_>>(), sieve(100));
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let small = sieve(10_000);
+        for n in 0..10_000 {
+            assert_eq!(is_prime(n as u64), small.binary_search(&n).is_ok(), "{n}");
+        }
+
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime((1 << 61) - 1));
+        assert!(is_prime(18_446_744_073_709_551_557)); // largest u64 prime
+        // strong pseudoprime to bases 2, 3, 5, 7, 11, 13, 17, 19, 23
+        assert!(!is_prime(3_825_123_056_546_413_051));
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorize(1_000_000_007 * 998_244_353),
+            vec![(998_244_353, 1), (1_000_000_007, 1)]
+        );
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65_537, 1),
+                (6_700_417, 1)
+            ]
+        );
+        assert_eq!(
+            factorize(4_294_967_291 * 4_294_967_291),
+            vec![(4_294_967_291, 2)]
+        );
+    }
+
+    #[test]
+    fn test_divisors() {
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(0), vec![]);
+        assert_eq!(divisors(10_551_264).len(), 48);
+        assert_eq!(divisor_sum(12), 28);
+        assert_eq!(divisor_sum(0), 0);
+        assert_eq!(divisor_sum(10_551_264), divisors(10_551_264).iter().sum());
+
+        let sums = divisor_sum_sieve(100);
+        for (n, &sum) in sums.iter().enumerate() {
+            assert_eq!(sum, divisor_sum(n as u64));
+        }
+    }
+
+    #[test]
+    fn test_totient() {
+        assert_eq!(totient(0), 0);
+        assert_eq!(totient(1), 1);
+        assert_eq!(totient(9), 6);
+        assert_eq!(totient(36), 12);
+        assert_eq!(totient(1_000_000_007), 1_000_000_006);
+    }
+}
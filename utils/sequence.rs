@@ -0,0 +1,316 @@
+use super::MyInteger;
+use num::{Signed, Zero, rational::Ratio};
+
+/// Returns the differences between consecutive elements, so `len - 1` elements.
+pub fn differences<T>(samples: &[T]) -> Vec<T>
+where
+    T: MyInteger,
+{
+    samples
+        .windows(2)
+        .map(|w| w[1].clone() - w[0].clone())
+        .collect()
+}
+
+/// Detects the degree of the polynomial that generated `samples` (taken at evenly spaced points)
+/// by taking finite differences until they are constant.
+///
+/// Returns `None` if there aren't enough samples to confirm a degree, which needs at least
+/// `degree + 2` samples.
+pub fn polynomial_degree<T>(samples: &[T]) -> Option<usize>
+where
+    T: MyInteger,
+{
+    let mut diff = samples.to_vec();
+    let mut degree = 0;
+
+    while diff.len() >= 2 {
+        if diff.windows(2).all(|w| w[0] == w[1]) {
+            return Some(degree);
+        }
+        diff = differences(&diff);
+        degree += 1;
+    }
+
+    None
+}
+
+/// Evaluates the polynomial through `samples`, where `samples[i] = f(i)`, at any `n` (including
+/// negative ones) using Newton's forward difference formula: `f(n) = sum(C(n, k) * Δ^k f(0))`.
+///
+/// The result is exact as long as the polynomial has degree `< samples.len()`.
+pub fn extrapolate_polynomial<T>(samples: &[T], n: T) -> T
+where
+    T: MyInteger + Signed,
+{
+    let mut diff = samples.to_vec();
+    let mut binomial = T::one(); // C(n, k)
+    let mut k = T::zero();
+    let mut result = T::zero();
+
+    while let Some(first) = diff.first() {
+        result = result + binomial.clone() * first;
+
+        // stop at the degree of the polynomial, the binomials past it can overflow even when the
+        // result fits
+        diff = differences(&diff);
+        if diff.iter().all(Zero::is_zero) {
+            break;
+        }
+
+        // C(n, k + 1) = C(n, k) * (n - k) / (k + 1), which always divides exactly
+        binomial = binomial * (n.clone() - k.clone());
+        k = k + T::one();
+        binomial = binomial / k.clone();
+    }
+
+    result
+}
+
+/// Evaluates the unique polynomial of degree `< points.len()` passing through `points` at `x`,
+/// using Lagrange interpolation. The x-coordinates don't have to be evenly spaced, but must be
+/// distinct.
+pub fn lagrange_interpolate<T>(points: &[(T, T)], x: T) -> Ratio<T>
+where
+    T: MyInteger + Signed,
+{
+    points
+        .iter()
+        .enumerate()
+        .fold(Ratio::zero(), |acc, (i, (xi, yi))| {
+            let basis = points.iter().enumerate().filter(|&(j, _)| i != j).fold(
+                Ratio::from_integer(yi.clone()),
+                |basis, (_, (xj, _))| {
+                    basis * Ratio::new(x.clone() - xj.clone(), xi.clone() - xj.clone())
+                },
+            );
+            acc + basis
+        })
+}
+
+/// Finds the shortest linear recurrence `s(n) = c[0] * s(n - 1) + ... + c[L - 1] * s(n - L)` that
+/// generates `samples`, using the Berlekamp–Massey algorithm over the rationals.
+///
+/// A recurrence of length `L` needs at least `2 * L` samples to be found reliably.
+pub fn berlekamp_massey<T>(samples: &[T]) -> Vec<Ratio<T>>
+where
+    T: MyInteger + Signed,
+{
+    let samples: Vec<Ratio<T>> = samples
+        .iter()
+        .map(|s| Ratio::from_integer(s.clone()))
+        .collect();
+
+    // connection polynomial c(x) = 1 + c[1] x + ... + c[l] x^l, and b(x) is the connection
+    // polynomial from before the last length change
+    let mut c = vec![Ratio::from_integer(T::one())];
+    let mut b = c.clone();
+    let mut b_discrepancy = Ratio::from_integer(T::one());
+    let mut l = 0;
+    let mut shift = 1;
+
+    for n in 0..samples.len() {
+        let discrepancy = (1..=l).fold(samples[n].clone(), |acc, i| {
+            acc + c[i].clone() * samples[n - i].clone()
+        });
+
+        if discrepancy.is_zero() {
+            shift += 1;
+            continue;
+        }
+
+        let coef = discrepancy.clone() / b_discrepancy.clone();
+        let previous = c.clone();
+        if c.len() < b.len() + shift {
+            c.resize(b.len() + shift, Ratio::zero());
+        }
+        for (i, bi) in b.iter().enumerate() {
+            c[i + shift] = c[i + shift].clone() - coef.clone() * bi.clone();
+        }
+
+        if 2 * l <= n {
+            l = n + 1 - l;
+            b = previous;
+            b_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    c.resize(l + 1, Ratio::zero());
+    c.into_iter().skip(1).map(|x| -x).collect()
+}
+
+/// Computes `a * b mod (x^L - c[0] x^(L - 1) - ... - c[L - 1])`.
+fn mul_mod_characteristic<T>(a: &[Ratio<T>], b: &[Ratio<T>], c: &[Ratio<T>]) -> Vec<Ratio<T>>
+where
+    T: MyInteger + Signed,
+{
+    let len = c.len();
+    let mut product = vec![Ratio::zero(); 2 * len - 1];
+    for (i, ai) in a.iter().enumerate() {
+        for (j, bj) in b.iter().enumerate() {
+            product[i + j] = product[i + j].clone() + ai.clone() * bj.clone();
+        }
+    }
+
+    // x^k = c[0] x^(k - 1) + ... + c[L - 1] x^(k - L)
+    for k in (len..product.len()).rev() {
+        let top = std::mem::replace(&mut product[k], Ratio::zero());
+        for (i, ci) in c.iter().enumerate() {
+            product[k - 1 - i] = product[k - 1 - i].clone() + top.clone() * ci.clone();
+        }
+    }
+
+    product.truncate(len);
+    product
+}
+
+/// Finds the linear recurrence generating `samples` with [`berlekamp_massey`], and evaluates term
+/// `n` (starting at 0) in `O(L^2 log n)` by computing `x^n` modulo the characteristic polynomial.
+///
+/// The intermediate polynomial coefficients can be much larger than the result, so use `i128` or
+/// `BigInt` for large `n`.
+pub fn extrapolate_recurrence<T>(samples: &[T], n: u64) -> Ratio<T>
+where
+    T: MyInteger + Signed,
+{
+    if let Some(s) = usize::try_from(n).ok().and_then(|n| samples.get(n)) {
+        return Ratio::from_integer(s.clone());
+    }
+
+    let c = berlekamp_massey(samples);
+    if c.is_empty() {
+        return Ratio::zero();
+    }
+
+    // x^n = sum(r[i] x^i), so s(n) = sum(r[i] s(i))
+    let one = Ratio::from_integer(T::one());
+    let mut result = vec![Ratio::zero(); c.len()];
+    result[0] = one.clone();
+    let mut base = vec![Ratio::zero(); c.len()];
+    if c.len() == 1 {
+        base[0] = c[0].clone();
+    } else {
+        base[1] = one;
+    }
+
+    let mut exp = n;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod_characteristic(&result, &base, &c);
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul_mod_characteristic(&base, &base, &c);
+        }
+    }
+
+    result
+        .into_iter()
+        .zip(samples)
+        .fold(Ratio::zero(), |acc, (r, s)| {
+            acc + r * Ratio::from_integer(s.clone())
+        })
+}
+
+#[cfg(feature = "common_test")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polynomial_degree() {
+        assert_eq!(polynomial_degree(&[0, 3, 6, 9, 12, 15]), Some(1));
+        assert_eq!(polynomial_degree(&[1, 3, 6, 10, 15, 21]), Some(2));
+        assert_eq!(polynomial_degree(&[10, 13, 16, 21, 30, 45]), Some(3));
+        assert_eq!(polynomial_degree(&[7, 7]), Some(0));
+        assert_eq!(polynomial_degree(&[1, 2, 4]), None);
+        assert_eq!(polynomial_degree::<i32>(&[]), None);
+    }
+
+    #[test]
+    fn test_extrapolate_polynomial() {
+        // Oasis histories, forwards and backwards
+        for (history, next, prev) in [
+            (vec![0, 3, 6, 9, 12, 15], 18, -3),
+            (vec![1, 3, 6, 10, 15, 21], 28, 0),
+            (vec![10, 13, 16, 21, 30, 45], 68, 5),
+        ] {
+            assert_eq!(extrapolate_polynomial(&history, history.len() as i64), next);
+            assert_eq!(extrapolate_polynomial(&history, -1), prev);
+        }
+
+        // n^3 - 2n far out
+        let samples: Vec<i128> = (0..4).map(|n| n * n * n - 2 * n).collect();
+        let n = 26_501_365;
+        assert_eq!(extrapolate_polynomial(&samples, n), n * n * n - 2 * n);
+
+        // more samples than the degree needs, in i64
+        let f = |x: i64| 3 * x * x + 2 * x + 1;
+        let samples: Vec<i64> = (0..6).map(f).collect();
+        assert_eq!(extrapolate_polynomial(&samples, 202_300), f(202_300));
+        assert_eq!(
+            extrapolate_polynomial(&samples[..4], 26_501_365),
+            f(26_501_365)
+        );
+        assert_eq!(extrapolate_polynomial::<i64>(&[], 10), 0);
+    }
+
+    #[test]
+    fn test_lagrange_interpolate() {
+        // Garden plot style samples at 65 + 131 * k
+        let f = |x: i128| 15_000 * x * x - 7_000 * x + 3_881;
+        let points: Vec<(i128, i128)> = (0..3).map(|k| 65 + 131 * k).map(|x| (x, f(x))).collect();
+        assert_eq!(
+            lagrange_interpolate(&points, 26_501_365),
+            Ratio::from_integer(f(26_501_365))
+        );
+
+        // y = x / 2 through (0, 0) and (2, 1)
+        assert_eq!(lagrange_interpolate(&[(0, 0), (2, 1)], 1), Ratio::new(1, 2));
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        let r = |v: &[i64]| {
+            v.iter()
+                .map(|&x| Ratio::from_integer(x))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(berlekamp_massey(&[0, 1, 1, 2, 3, 5, 8, 13]), r(&[1, 1]));
+        // squares are a polynomial, so s(n) = 3s(n - 1) - 3s(n - 2) + s(n - 3)
+        assert_eq!(berlekamp_massey(&[0, 1, 4, 9, 16, 25, 36]), r(&[3, -3, 1]));
+        assert_eq!(berlekamp_massey(&[3, 6, 12, 24]), r(&[2]));
+        assert_eq!(berlekamp_massey(&[0, 0, 0]), r(&[]));
+        // s(n) = (s(n - 1) + s(n - 2)) / 2
+        assert_eq!(
+            berlekamp_massey(&[16, 0, 8, 4, 6, 5]),
+            vec![Ratio::new(1, 2), Ratio::new(1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_extrapolate_recurrence() {
+        let fib = [0i128, 1, 1, 2, 3, 5, 8, 13];
+        assert_eq!(extrapolate_recurrence(&fib, 5), Ratio::from_integer(5));
+        assert_eq!(
+            extrapolate_recurrence(&fib, 150),
+            Ratio::from_integer(9_969_216_677_189_303_386_214_405_760_200)
+        );
+
+        let squares = [0i128, 1, 4, 9, 16, 25];
+        assert_eq!(
+            extrapolate_recurrence(&squares, 1_000_000),
+            Ratio::from_integer(1_000_000_000_000)
+        );
+
+        assert_eq!(
+            extrapolate_recurrence(&[3, 6, 12, 24], 10),
+            Ratio::from_integer(3 * 1024)
+        );
+        assert_eq!(extrapolate_recurrence(&[0, 0, 0], 10), Ratio::zero());
+    }
+}